@@ -0,0 +1,331 @@
+// Std lib
+use std::{
+    collections::BTreeMap,
+    env,
+    process::{self, Command as Process},
+    str::FromStr,
+};
+
+// Third party
+use envy_store::{Options, ParameterPath, ParameterSource, SsmSource};
+use structopt::StructOpt;
+use tokio::runtime::current_thread::Runtime;
+
+/// Formats parameters may be exported in
+enum Format {
+    Dotenv,
+    Shell,
+    Json,
+    Yaml,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dotenv" => Ok(Format::Dotenv),
+            "shell" => Ok(Format::Shell),
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            other => Err(format!(
+                "unknown format {}, expected dotenv, shell, json or yaml",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "envy-store",
+    about = "Resolves AWS Parameter Store values for use outside of Rust"
+)]
+enum Command {
+    /// Prints the parameters stored under a path prefix
+    #[structopt(name = "export")]
+    Export {
+        /// Output format: dotenv, shell, json or yaml
+        #[structopt(short = "f", long = "format", default_value = "dotenv")]
+        format: Format,
+        /// Parameter path prefix, e.g. /sweet-app/prod
+        prefix: String,
+    },
+    /// Runs a command with the parameters stored under a path prefix as environment variables
+    ///
+    /// Names relative to the prefix are converted to upper snake case, e.g. db/host-name
+    /// becomes DB_HOST_NAME
+    #[structopt(name = "exec")]
+    Exec {
+        /// Parameter path prefix, e.g. /sweet-app/prod
+        #[structopt(short = "p", long = "prefix")]
+        prefix: String,
+        /// Prepended to the name of each environment variable, e.g. APP_
+        #[structopt(short = "k", long = "key-prefix", default_value = "")]
+        key_prefix: String,
+        /// Keeps existing environment variables rather than replacing them with parameters
+        #[structopt(long = "keep-env")]
+        keep_env: bool,
+        /// The command to run and its arguments
+        #[structopt(raw(required = "true"))]
+        command: Vec<String>,
+    },
+}
+
+fn main() {
+    let result = match Command::from_args() {
+        Command::Export { format, prefix } => {
+            fetch(&prefix).and_then(|values| export(&format, &values))
+        }
+        Command::Exec {
+            prefix,
+            key_prefix,
+            keep_env,
+            command,
+        } => fetch(&prefix)
+            .and_then(|values| vars(&values, &key_prefix, keep_env))
+            .and_then(|vars| exec(&command, vars)),
+    };
+    match result {
+        Ok(output) => print!("{}", output),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Resolves parameters under `prefix` keyed by their names relative to `prefix`
+fn fetch(prefix: &str) -> Result<BTreeMap<String, String>, String> {
+    let prefix = ParameterPath::new(prefix).map_err(|err| err.to_string())?;
+    let mut runtime = Runtime::new().map_err(|err| err.to_string())?;
+    let parameters = runtime
+        .block_on(SsmSource::default().parameters(prefix.as_str(), &Options::default()))
+        .map_err(|err| err.to_string())?;
+    Ok(parameters
+        .into_iter()
+        .filter_map(|param| {
+            let key = prefix.relative(&param.name)?.to_string();
+            Some((key, param.value))
+        })
+        .collect())
+}
+
+fn export(
+    format: &Format,
+    values: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    match format {
+        Format::Dotenv => Ok(lines(env_vars(values, "")?, |name, value| {
+            format!("{}={}", name, double_quoted(value))
+        })),
+        Format::Shell => Ok(lines(env_vars(values, "")?, |name, value| {
+            format!("export {}={}", name, single_quoted(value))
+        })),
+        Format::Json => serde_json::to_string_pretty(values)
+            .map(|json| json + "\n")
+            .map_err(|err| err.to_string()),
+        Format::Yaml => serde_yaml::to_string(values)
+            .map(|yaml| yaml + "\n")
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// Returns the environment variables to set for `values`
+fn vars(
+    values: &BTreeMap<String, String>,
+    key_prefix: &str,
+    keep_env: bool,
+) -> Result<Vec<(String, String)>, String> {
+    Ok(env_vars(values, key_prefix)?
+        .into_iter()
+        .filter(|(name, _)| !keep_env || env::var_os(name).is_none())
+        .collect())
+}
+
+/// Names each of `values` as an environment variable, failing when two keys
+/// convert to the same name
+fn env_vars(
+    values: &BTreeMap<String, String>,
+    key_prefix: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let mut keys = BTreeMap::new();
+    let mut vars = Vec::new();
+    for (key, value) in values {
+        let name = format!("{}{}", key_prefix, env_name(key));
+        if let Some(other) = keys.insert(name.clone(), key) {
+            return Err(format!(
+                "parameters {} and {} both map to the environment variable {}",
+                other, key, name
+            ));
+        }
+        vars.push((name, value.clone()));
+    }
+    Ok(vars)
+}
+
+/// Replaces the current process with `command`, only returning on failure
+#[cfg(unix)]
+fn exec(
+    command: &[String],
+    vars: Vec<(String, String)>,
+) -> Result<String, String> {
+    use std::os::unix::process::CommandExt;
+    let err = Process::new(&command[0])
+        .args(&command[1..])
+        .envs(vars)
+        .exec();
+    Err(format!("failed to run {}: {}", command[0], err))
+}
+
+/// Runs `command` to completion, exiting with its status
+#[cfg(not(unix))]
+fn exec(
+    command: &[String],
+    vars: Vec<(String, String)>,
+) -> Result<String, String> {
+    let status = Process::new(&command[0])
+        .args(&command[1..])
+        .envs(vars)
+        .status()
+        .map_err(|err| format!("failed to run {}: {}", command[0], err))?;
+    process::exit(status.code().unwrap_or(1))
+}
+
+fn lines<F>(
+    vars: Vec<(String, String)>,
+    line: F,
+) -> String
+where
+    F: Fn(&str, &str) -> String,
+{
+    vars.iter()
+        .map(|(name, value)| line(name, value) + "\n")
+        .collect()
+}
+
+/// Converts a parameter key, e.g. `db/host-name`, into an environment variable
+/// name, e.g. `DB_HOST_NAME`
+fn env_name(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Quotes `value` for dotenv files, escaping characters which would otherwise be
+/// interpolated, e.g. `$HOME` or backticks
+fn double_quoted(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('`', "\\`")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn single_quoted(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, vars, Format};
+    use std::{collections::BTreeMap, env};
+
+    fn values() -> BTreeMap<String, String> {
+        vec![
+            ("db/host-name".to_string(), "localhost".to_string()),
+            ("greeting".to_string(), "it's \"hi\"".to_string()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn exports_env_lines() {
+        assert_eq!(
+            Ok("DB_HOST_NAME=\"localhost\"\nGREETING=\"it's \\\"hi\\\"\"\n".to_string()),
+            export(&Format::Dotenv, &values())
+        );
+        assert_eq!(
+            Ok("export DB_HOST_NAME='localhost'\nexport GREETING='it'\\''s \"hi\"'\n".to_string()),
+            export(&Format::Shell, &values())
+        );
+    }
+
+    #[test]
+    fn escapes_interpolation_in_env_lines() {
+        let values = vec![("token".to_string(), "$HOME`id`".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            Ok("TOKEN=\"\\$HOME\\`id\\`\"\n".to_string()),
+            export(&Format::Dotenv, &values)
+        );
+        assert_eq!(
+            Ok("export TOKEN='$HOME`id`'\n".to_string()),
+            export(&Format::Shell, &values)
+        );
+    }
+
+    #[test]
+    fn prefixes_vars_and_optionally_keeps_env() {
+        env::set_var("ENVY_STORE_TEST_GREETING", "hello");
+        assert_eq!(
+            vec![
+                (
+                    "ENVY_STORE_TEST_DB_HOST_NAME".to_string(),
+                    "localhost".to_string()
+                ),
+                (
+                    "ENVY_STORE_TEST_GREETING".to_string(),
+                    "it's \"hi\"".to_string()
+                ),
+            ],
+            vars(&values(), "ENVY_STORE_TEST_", false).unwrap()
+        );
+        assert_eq!(
+            vec![(
+                "ENVY_STORE_TEST_DB_HOST_NAME".to_string(),
+                "localhost".to_string()
+            )],
+            vars(&values(), "ENVY_STORE_TEST_", true).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_colliding_vars() {
+        let values = vec![
+            ("db-host".to_string(), "a".to_string()),
+            ("db/host".to_string(), "b".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            Err(
+                "parameters db-host and db/host both map to the environment variable DB_HOST"
+                    .into()
+            ),
+            vars(&values, "", false)
+        );
+        assert!(export(&Format::Dotenv, &values).is_err());
+    }
+
+    #[test]
+    fn exports_documents_keyed_by_name() {
+        assert_eq!(
+            Ok(
+                "{\n  \"db/host-name\": \"localhost\",\n  \"greeting\": \"it's \\\"hi\\\"\"\n}\n"
+                    .to_string()
+            ),
+            export(&Format::Json, &values())
+        );
+    }
+}
@@ -0,0 +1,243 @@
+// Std lib
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Third party
+use futures::{future, Future};
+
+// Ours
+use crate::{Error, Options, Parameter, ParameterSource, Parameters};
+
+type Entries = HashMap<String, Vec<(Options, Instant, Vec<Parameter>)>>;
+
+/// Caches the parameters resolved by another source for a period of time
+///
+/// Parameters are cached by path prefix and [Options](struct.Options.html). Clones share
+/// the same cache, so a single `CachedSource` may be cloned into each `EnvyStore` loading
+/// overlapping prefixes. Parameters resolved by name are passed through to the
+/// underlying source uncached
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{CachedSource, EnvyStore, SsmSource};
+/// use std::{collections::HashMap, time::Duration};
+///
+/// let source = CachedSource::new(SsmSource::default(), Duration::from_secs(60));
+/// let first = EnvyStore::builder()
+///   .source(source.clone())
+///   .load::<HashMap<String, String>, _>("/demo");
+/// // resolved from the cache for the next 60 seconds
+/// let second = EnvyStore::builder()
+///   .source(source.clone())
+///   .load::<HashMap<String, String>, _>("/demo");
+/// ```
+#[derive(Clone)]
+pub struct CachedSource<S> {
+    source: S,
+    ttl: Duration,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl<S> CachedSource<S> {
+    /// Creates a new source caching parameters resolved by `source` for `ttl`
+    pub fn new(
+        source: S,
+        ttl: Duration,
+    ) -> Self {
+        CachedSource {
+            source,
+            ttl,
+            entries: Arc::default(),
+        }
+    }
+
+    /// Removes all cached parameters
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    fn cached(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Option<Vec<Parameter>> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(path_prefix)?
+            .iter()
+            .find(|(cached, at, _)| cached == options && at.elapsed() < self.ttl)
+            .map(|(_, _, parameters)| parameters.clone())
+    }
+}
+
+impl<S> CachedSource<S>
+where
+    S: ParameterSource,
+{
+    /// Removes cached parameters for `path_prefix`, regardless of the options
+    /// used to resolve them
+    ///
+    /// `path_prefix` is normalized the same way prefixes are when loading, so
+    /// `/app/` invalidates parameters cached for `/app`
+    pub fn invalidate<P>(
+        &self,
+        path_prefix: P,
+    ) where
+        P: AsRef<Path>,
+    {
+        let prefix = match self.source.validate_prefix(path_prefix.as_ref()) {
+            Ok(prefix) => prefix,
+            // nothing is cached for prefixes which can not be loaded
+            Err(_) => return,
+        };
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(&prefix);
+        }
+    }
+}
+
+impl<S> ParameterSource for CachedSource<S>
+where
+    S: ParameterSource,
+{
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters {
+        if let Some(parameters) = self.cached(path_prefix, options) {
+            return Box::new(future::ok(parameters));
+        }
+        let entries = self.entries.clone();
+        let prefix = path_prefix.to_string();
+        let options = options.clone();
+        Box::new(
+            self.source
+                .parameters(&prefix, &options)
+                .map(move |parameters| {
+                    if let Ok(mut entries) = entries.lock() {
+                        let cached = entries.entry(prefix).or_insert_with(Vec::new);
+                        cached.retain(|(cached, _, _)| cached != &options);
+                        cached.push((options, Instant::now(), parameters.clone()));
+                    }
+                    parameters
+                }),
+        )
+    }
+
+    fn validate_prefix(
+        &self,
+        path_prefix: &Path,
+    ) -> Result<String, Error> {
+        self.source.validate_prefix(path_prefix)
+    }
+
+    fn parameters_named(
+        &self,
+        names: &[String],
+        options: &Options,
+    ) -> Parameters {
+        self.source.parameters_named(names, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachedSource;
+    use crate::{Options, Parameter, ParameterSource, Parameters};
+    use futures::{future, Future};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    #[derive(Clone, Default)]
+    struct Counting(Arc<AtomicUsize>);
+
+    impl ParameterSource for Counting {
+        fn parameters(
+            &self,
+            path_prefix: &str,
+            options: &Options,
+        ) -> Parameters {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            vec![Parameter::new("/app/foo", "bar")].parameters(path_prefix, options)
+        }
+    }
+
+    #[test]
+    fn caches_until_invalidated() {
+        let counting = Counting::default();
+        let source = CachedSource::new(counting.clone(), Duration::from_secs(60));
+        let options = Options::default();
+        for _ in 0..2 {
+            assert_eq!(
+                Ok(vec![Parameter::new("/app/foo", "bar")]),
+                source.parameters("/app", &options).wait()
+            );
+        }
+        assert_eq!(1, counting.0.load(Ordering::SeqCst));
+        source.invalidate("/app/");
+        source.parameters("/app", &options).wait().unwrap();
+        assert_eq!(2, counting.0.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn forwards_named_lookups() {
+        struct Named;
+
+        impl ParameterSource for Named {
+            fn parameters(
+                &self,
+                _: &str,
+                _: &Options,
+            ) -> Parameters {
+                Box::new(future::ok(Vec::new()))
+            }
+
+            fn parameters_named(
+                &self,
+                names: &[String],
+                _: &Options,
+            ) -> Parameters {
+                Box::new(future::ok(
+                    names
+                        .iter()
+                        .map(|name| Parameter::new(name, "named"))
+                        .collect(),
+                ))
+            }
+        }
+
+        let source = CachedSource::new(Named, Duration::from_secs(60));
+        assert_eq!(
+            Ok(vec![Parameter::new("/app/foo", "named")]),
+            source
+                .parameters_named(&["/app/foo".to_string()], &Options::default())
+                .wait()
+        );
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let counting = Counting::default();
+        let source = CachedSource::new(counting.clone(), Duration::from_secs(0));
+        for _ in 0..2 {
+            source
+                .parameters("/app", &Options::default())
+                .wait()
+                .unwrap();
+        }
+        assert_eq!(2, counting.0.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,637 @@
+// Std lib
+use std::{
+    collections::BTreeMap,
+    iter::{empty, once},
+};
+
+// Third party
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+    DeserializeOwned, IntoDeserializer,
+};
+
+// Ours
+use crate::{DeserializeError as Error, Normalize, ParameterType};
+
+/// A tree of parameter values keyed by the path segments
+/// that follow a path prefix
+///
+/// `/app/prod/db/host` resolved under `/app/prod` becomes
+/// a `db` branch holding a `host` leaf
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Node {
+    Leaf(Leaf),
+    Branch(Branch),
+    Placeholder(Placeholder),
+}
+
+/// A single parameter value, the full name and type it was resolved from
+/// and its location below a prefix
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Leaf {
+    name: String,
+    type_: Option<ParameterType>,
+    path: Vec<String>,
+    value: String,
+    strict: bool,
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    json: bool,
+}
+
+/// Controls how values are deserialized
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Settings {
+    /// Whether sequences must be resolved from `StringList` parameters
+    pub(crate) strict: bool,
+    /// Paths of values holding JSON documents, before normalization
+    pub(crate) json_keys: Vec<Vec<String>>,
+    /// How parameter names are converted into keys
+    pub(crate) normalize: Normalize,
+}
+
+/// Nodes nested below a location
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Branch {
+    path: Vec<String>,
+    children: BTreeMap<String, Node>,
+}
+
+/// Stands in for a value which is missing or failed to deserialize so that
+/// deserialization may continue past it, producing an empty or zero value
+/// of whichever type is requested
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Placeholder {
+    path: Vec<String>,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Node::Branch(Branch::default())
+    }
+}
+
+/// A resolved value, the name it was resolved from and the
+/// path segments locating it below a prefix
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) path: Vec<String>,
+    pub(crate) value: String,
+    pub(crate) type_: Option<ParameterType>,
+}
+
+impl Node {
+    /// Inserts an entry's value at the location described by its path,
+    /// replacing any value previously inserted there
+    pub(crate) fn insert(
+        &mut self,
+        entry: Entry,
+        settings: &Settings,
+    ) -> Result<(), Error> {
+        let Entry {
+            name,
+            path,
+            value,
+            type_,
+        } = entry;
+        let path = settings.normalize.key(&path);
+        let mut node = self;
+        for (depth, key) in path.iter().enumerate() {
+            let branch = match node {
+                Node::Branch(branch) => branch,
+                Node::Leaf(leaf) => return Err(conflict(&leaf.name)),
+                Node::Placeholder(_) => return Ok(()),
+            };
+            if depth + 1 == path.len() {
+                return match branch.children.get(key) {
+                    Some(Node::Branch(_)) => Err(conflict(&name)),
+                    _ => {
+                        let leaf = Leaf {
+                            name,
+                            type_,
+                            path: path.clone(),
+                            value,
+                            strict: settings.strict,
+                            json: settings
+                                .json_keys
+                                .iter()
+                                .any(|key| settings.normalize.key(key) == path),
+                        };
+                        branch.children.insert(key.clone(), Node::Leaf(leaf));
+                        Ok(())
+                    }
+                };
+            }
+            node = branch.children.entry(key.clone()).or_insert_with(|| {
+                Node::Branch(Branch {
+                    path: path[..=depth].to_vec(),
+                    children: BTreeMap::new(),
+                })
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Node {
+    /// Replaces the node at `path`, creating any branches leading to it,
+    /// with a placeholder
+    pub(crate) fn placeholder(
+        &mut self,
+        path: &[String],
+    ) {
+        let mut node = self;
+        for (depth, key) in path.iter().enumerate() {
+            if let Node::Placeholder(_) = node {
+                *node = Node::Branch(Branch {
+                    path: path[..depth].to_vec(),
+                    children: BTreeMap::new(),
+                });
+            }
+            node = match node {
+                Node::Branch(branch) => branch.children.entry(key.clone()).or_insert_with(|| {
+                    Node::Placeholder(Placeholder {
+                        path: path[..=depth].to_vec(),
+                    })
+                }),
+                _ => return,
+            };
+        }
+        *node = Node::Placeholder(Placeholder {
+            path: path.to_vec(),
+        });
+    }
+}
+
+fn conflict(name: &str) -> Error {
+    de::Error::custom(format_args!(
+        "parameter {} has a value as well as nested parameters beneath it",
+        name
+    ))
+}
+
+impl Leaf {
+    /// Returns a function attaching this leaf's parameter to errors
+    /// raised while deserializing it
+    fn locate(&self) -> impl FnOnce(Error) -> Error {
+        let name = self.name.clone();
+        let type_ = self.type_;
+        let path = self.path.clone();
+        move |err| {
+            // avoid leaking secrets into logs
+            let err = match type_ {
+                Some(ParameterType::SecureString) => err.redacted(),
+                _ => err,
+            };
+            err.parameter(name, type_, path)
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Node {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Leaf {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_to_leaf {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+                where V: de::Visitor<'de>
+            {
+                match self {
+                    Node::Leaf(leaf) => {
+                        let locate = leaf.locate();
+                        leaf.$method(visitor).map_err(locate)
+                    }
+                    Node::Placeholder(placeholder) => placeholder.$method(visitor),
+                    branch => branch.deserialize_any(visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Node {
+    type Error = Error;
+
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(leaf) => {
+                let locate = leaf.locate();
+                leaf.deserialize_any(visitor).map_err(locate)
+            }
+            Node::Branch(Branch { path, children }) => visitor
+                .visit_map(MapDeserializer::new(children.into_iter()))
+                .map_err(|err| err.within(&path)),
+            Node::Placeholder(placeholder) => placeholder.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Placeholder(_) => visitor.visit_none(),
+            node => visitor.visit_some(node),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Placeholder(placeholder) => placeholder.deserialize_struct(name, fields, visitor),
+            node => node.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(leaf) => {
+                let locate = leaf.locate();
+                leaf.deserialize_enum(name, variants, visitor)
+                    .map_err(locate)
+            }
+            Node::Placeholder(placeholder) => placeholder.deserialize_enum(name, variants, visitor),
+            branch => branch.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_leaf! {
+        deserialize_bool deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_f32 deserialize_f64 deserialize_seq deserialize_char deserialize_str
+        deserialize_string deserialize_unit deserialize_bytes deserialize_byte_buf
+        deserialize_map deserialize_identifier
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit_struct tuple_struct tuple ignored_any
+    }
+}
+
+/// Returns early, deserializing from a leaf's parsed value, when the leaf holds a JSON document
+macro_rules! deserialize_json {
+    ($leaf:ident.$method:ident($($arg:expr),*)) => {
+        #[cfg(feature = "json")]
+        {
+            if $leaf.json {
+                return $leaf.parse_json()?.$method($($arg),*).map_err(de::Error::custom);
+            }
+        }
+    };
+}
+
+impl Leaf {
+    #[cfg(feature = "json")]
+    fn parse_json(&self) -> Result<serde_json::Value, Error> {
+        serde_json::from_str(&self.value)
+            .map_err(|e| de::Error::custom(format_args!("{} while parsing JSON value", e)))
+    }
+}
+
+macro_rules! forward_parsed_values {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+                where V: de::Visitor<'de>
+            {
+                deserialize_json!(self.$method(visitor));
+                match self.value.parse::<$ty>() {
+                    Ok(val) => val.into_deserializer().$method(visitor),
+                    // parse errors do not quote the value, so are located rather than redacted
+                    Err(e) if self.type_ == Some(ParameterType::SecureString) => {
+                        let err: Error = de::Error::custom(e);
+                        Err(err.parameter(self.name, self.type_, self.path))
+                    }
+                    Err(e) => Err(de::Error::custom(format_args!("{} while parsing value '{}'", e, self.value)))
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Leaf {
+    type Error = Error;
+
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        deserialize_json!(self.deserialize_any(visitor));
+        self.value.into_deserializer().deserialize_any(visitor)
+    }
+
+    fn deserialize_seq<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        deserialize_json!(self.deserialize_seq(visitor));
+        // only lists, or values of an unknown type, are split
+        match self.type_ {
+            Some(ParameterType::StringList) | None => (),
+            Some(type_) if self.strict => {
+                return Err(de::Error::custom(format_args!(
+                    "expected a StringList parameter but found a {} parameter",
+                    type_
+                )))
+            }
+            Some(_) => return SeqDeserializer::new(once(self)).deserialize_seq(visitor),
+        }
+        // an empty value is an empty list rather than a list of one empty value
+        if self.value.is_empty() {
+            SeqDeserializer::new(empty::<Leaf>()).deserialize_seq(visitor)
+        } else {
+            let Leaf {
+                name,
+                type_,
+                path,
+                strict,
+                ..
+            } = &self;
+            let values = self
+                .value
+                .split(',')
+                .map(|value| Leaf {
+                    name: name.clone(),
+                    type_: *type_,
+                    path: path.clone(),
+                    value: value.to_owned(),
+                    strict: *strict,
+                    json: false,
+                })
+                .collect::<Vec<_>>();
+            SeqDeserializer::new(values.into_iter()).deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_option<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_parsed_values! {
+        bool => deserialize_bool,
+        u8 => deserialize_u8,
+        u16 => deserialize_u16,
+        u32 => deserialize_u32,
+        u64 => deserialize_u64,
+        i8 => deserialize_i8,
+        i16 => deserialize_i16,
+        i32 => deserialize_i32,
+        i64 => deserialize_i64,
+        f32 => deserialize_f32,
+        f64 => deserialize_f64,
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[cfg_attr(not(feature = "json"), allow(unused_variables))]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        deserialize_json!(self.deserialize_enum(name, variants, visitor));
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string unit
+        bytes byte_buf map unit_struct tuple_struct
+        identifier tuple ignored_any struct
+    }
+}
+
+macro_rules! forward_default_values {
+    ($($method:ident => $visit:ident($($value:expr)?),)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+                where V: de::Visitor<'de>
+            {
+                visitor.$visit($($value)?)
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Placeholder {
+    type Error = Error;
+
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_seq<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::new(empty::<Node>()))
+    }
+
+    fn deserialize_map<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(MapDeserializer::new(empty::<(String, Node)>()))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let path = self.path;
+        visitor
+            .visit_map(MapDeserializer::new(empty::<(String, Node)>()))
+            .map_err(|err| err.within(&path))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match variants.first() {
+            Some(variant) => {
+                visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(*variant))
+            }
+            None => visitor.visit_unit(),
+        }
+    }
+
+    forward_default_values! {
+        deserialize_bool => visit_bool(false),
+        deserialize_u8 => visit_u8(0),
+        deserialize_u16 => visit_u16(0),
+        deserialize_u32 => visit_u32(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_i8 => visit_i8(0),
+        deserialize_i16 => visit_i16(0),
+        deserialize_i32 => visit_i32(0),
+        deserialize_i64 => visit_i64(0),
+        deserialize_f32 => visit_f32(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char('\0'),
+        deserialize_str => visit_str(""),
+        deserialize_string => visit_str(""),
+        deserialize_identifier => visit_str(""),
+        deserialize_bytes => visit_bytes(&[]),
+        deserialize_byte_buf => visit_bytes(&[]),
+        deserialize_option => visit_none(),
+        deserialize_unit => visit_unit(),
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit_struct tuple_struct tuple ignored_any
+    }
+}
+
+/// Returns the names of the fields of `T` when it is a struct
+pub(crate) fn fields<T>() -> &'static [&'static str]
+where
+    T: DeserializeOwned,
+{
+    let mut fields = None;
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields.unwrap_or_default()
+}
+
+/// Captures the names of the fields a struct requests, failing to deserialize anything
+struct FieldNames<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de, 'a> de::Deserializer<'de> for FieldNames<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(
+        self,
+        _: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom("expected a struct"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("captured fields"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
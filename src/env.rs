@@ -0,0 +1,195 @@
+// Std lib
+use std::env;
+
+// Ours
+use crate::{de::Entry, Normalize};
+
+/// Overlays process environment variables on resolved parameter store values
+///
+/// Variable names are lowercased and `__` separates levels of nesting, so with the
+/// prefix `APP_`, `APP_DB__HOST` provides the same value as a parameter named `db/host` below
+/// the path prefix. Variables which would replace a branch of nested parameters with a value,
+/// or nest a value below one, e.g. `USER` alongside parameters named `user/name`, are skipped
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{EnvOverlay, EnvyStore};
+/// use std::collections::HashMap;
+///
+/// // APP_FOO=bar replaces the value of /demo/foo
+/// let config = EnvyStore::builder()
+///   .env_overlay(EnvOverlay::prefixed("APP_"))
+///   .load::<HashMap<String, String>, _>("/demo");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnvOverlay {
+    prefix: Option<String>,
+    fallback: bool,
+}
+
+impl EnvOverlay {
+    /// Overlays all environment variables
+    pub fn new() -> Self {
+        EnvOverlay::default()
+    }
+
+    /// Overlays only environment variables whose names start with `prefix`.
+    /// The prefix is removed from names before they are matched with parameters
+    pub fn prefixed<P>(prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        EnvOverlay {
+            prefix: Some(prefix.into()),
+            ..EnvOverlay::default()
+        }
+    }
+
+    /// Uses environment variables only to fill in values missing from parameter store
+    /// rather than to override them
+    pub fn fallback(mut self) -> Self {
+        self.fallback = true;
+        self
+    }
+
+    /// Combines `entries` with those of the current process environment, skipping
+    /// variables whose keys conflict with those of entries or earlier variables
+    pub(crate) fn apply(
+        &self,
+        entries: Vec<Entry>,
+        normalize: &Normalize,
+    ) -> Vec<Entry> {
+        self.apply_vars(entries, env::vars(), normalize)
+    }
+
+    fn apply_vars<V>(
+        &self,
+        entries: Vec<Entry>,
+        vars: V,
+        normalize: &Normalize,
+    ) -> Vec<Entry>
+    where
+        V: IntoIterator<Item = (String, String)>,
+    {
+        let mut keys = entries
+            .iter()
+            .map(|entry| normalize.key(&entry.path))
+            .collect::<Vec<_>>();
+        let overlay = vars.into_iter().filter_map(|(name, value)| {
+            let key = match &self.prefix {
+                Some(prefix) if name.starts_with(prefix.as_str()) => &name[prefix.len()..],
+                Some(_) => return None,
+                None => &name[..],
+            };
+            let path = key
+                .split("__")
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>();
+            if path.is_empty() {
+                return None;
+            }
+            let key = normalize.key(&path);
+            if keys.iter().any(|other| conflicts(other, &key)) {
+                return None;
+            }
+            keys.push(key);
+            Some(Entry {
+                name,
+                path,
+                value,
+                type_: None,
+            })
+        });
+        if self.fallback {
+            overlay.chain(entries).collect()
+        } else {
+            entries.into_iter().chain(overlay).collect()
+        }
+    }
+}
+
+/// Returns true when one key is nested below the other, so that one would be a value
+/// and the other a branch
+fn conflicts(
+    a: &[String],
+    b: &[String],
+) -> bool {
+    a.len() != b.len() && (a.starts_with(b) || b.starts_with(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvOverlay;
+    use crate::{de::Entry, Normalize};
+
+    fn entry(
+        name: &str,
+        path: &[&str],
+        value: &str,
+    ) -> Entry {
+        Entry {
+            name: name.into(),
+            path: path.iter().map(|segment| segment.to_string()).collect(),
+            value: value.into(),
+            type_: None,
+        }
+    }
+
+    #[test]
+    fn overrides_with_prefixed_vars() {
+        let stored = vec![entry("/app/db/host", &["db", "host"], "db.example.com")];
+        assert_eq!(
+            vec![
+                entry("/app/db/host", &["db", "host"], "db.example.com"),
+                entry("APP_DB__HOST", &["db", "host"], "localhost"),
+            ],
+            EnvOverlay::prefixed("APP_").apply_vars(
+                stored,
+                vec![
+                    ("APP_DB__HOST".to_string(), "localhost".to_string()),
+                    ("HOME".to_string(), "/root".to_string()),
+                ],
+                &Normalize::default()
+            )
+        )
+    }
+
+    #[test]
+    fn falls_back_to_vars() {
+        let stored = vec![entry("/app/port", &["port"], "8080")];
+        assert_eq!(
+            vec![
+                entry("PORT", &["port"], "3000"),
+                entry("/app/port", &["port"], "8080"),
+            ],
+            EnvOverlay::new().fallback().apply_vars(
+                stored,
+                vec![("PORT".to_string(), "3000".to_string())],
+                &Normalize::default()
+            )
+        )
+    }
+
+    #[test]
+    fn skips_vars_conflicting_with_branches() {
+        let stored = vec![entry("/app/user/name", &["user", "name"], "admin")];
+        assert_eq!(
+            vec![
+                entry("/app/user/name", &["user", "name"], "admin"),
+                entry("PORT", &["port"], "3000"),
+            ],
+            EnvOverlay::new().apply_vars(
+                stored,
+                vec![
+                    ("USER".to_string(), "root".to_string()),
+                    ("USER__NAME__FIRST".to_string(), "ro".to_string()),
+                    ("PORT".to_string(), "3000".to_string()),
+                    ("PORT__NUMBER".to_string(), "3001".to_string()),
+                ],
+                &Normalize::default()
+            )
+        )
+    }
+}
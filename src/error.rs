@@ -1,17 +1,46 @@
 // Std lib
-use std::{error::Error as StdError, fmt};
+use std::{error::Error as StdError, fmt, time::Duration};
 
 // Third party
-use envy;
-use rusoto_ssm::GetParametersByPathError;
+use rusoto_ssm::{GetParametersByPathError, GetParametersError, PutParameterError};
+use serde::de;
+
+// Ours
+use crate::ParameterType;
+#[cfg(feature = "secretsmanager")]
+use crate::SecretsManagerError;
 
 /// Represents possible errors
 #[derive(Debug, PartialEq)]
 pub enum Error {
     /// Returned when parameter store request fails
     Store(GetParametersByPathError),
-    /// Returned with deserialization fails
-    Envy(envy::Error),
+    /// Returned when a parameter store request for parameters by name fails
+    Parameters(GetParametersError),
+    /// Returned with the names of parameters, or their selected versions, which do not exist
+    MissingParameters(Vec<String>),
+    /// Returned when a resolved value is missing or can not be deserialized
+    Deserialize(DeserializeError),
+    /// Returned when more than one value is missing or can not be deserialized
+    Aggregate(Vec<DeserializeError>),
+    /// Returned when a blocking load does not complete within its timeout
+    Timeout(Duration),
+    /// Returned when a runtime for a blocking load could not be started
+    Runtime(String),
+    /// Returned with a path prefix which is not a valid parameter store path and why
+    InvalidPath(String, String),
+    /// Returned with the names of two parameters which resolve to the same key
+    Collision(String, String),
+    /// Returned when a value can not be serialized into parameters
+    Serialize(String),
+    /// Returned when a parameter store request to write a parameter fails
+    Put(PutParameterError),
+    /// Returned when a parameter file can not be read or parsed
+    #[cfg(feature = "file")]
+    File(String),
+    /// Returned when a Secrets Manager request fails
+    #[cfg(feature = "secretsmanager")]
+    SecretsManager(SecretsManagerError),
 }
 
 impl From<GetParametersByPathError> for Error {
@@ -20,9 +49,21 @@ impl From<GetParametersByPathError> for Error {
     }
 }
 
-impl From<envy::Error> for Error {
-    fn from(err: envy::Error) -> Self {
-        Error::Envy(err)
+impl From<GetParametersError> for Error {
+    fn from(err: GetParametersError) -> Self {
+        Error::Parameters(err)
+    }
+}
+
+impl From<PutParameterError> for Error {
+    fn from(err: PutParameterError) -> Self {
+        Error::Put(err)
+    }
+}
+
+impl From<DeserializeError> for Error {
+    fn from(err: DeserializeError) -> Self {
+        Error::Deserialize(err)
     }
 }
 
@@ -30,14 +71,40 @@ impl StdError for Error {
     fn description(&self) -> &str {
         match self {
             Error::Store(e) => e.description(),
-            Error::Envy(e) => e.description(),
+            Error::Parameters(e) => e.description(),
+            Error::MissingParameters(_) => "missing parameters",
+            Error::Deserialize(e) => e.description(),
+            Error::Aggregate(_) => "failed to deserialize values",
+            Error::Timeout(_) => "timed out resolving parameters",
+            Error::Runtime(msg) => msg,
+            Error::InvalidPath(..) => "invalid parameter path",
+            Error::Collision(..) => "parameters resolve to the same key",
+            Error::Serialize(msg) => msg,
+            Error::Put(e) => e.description(),
+            #[cfg(feature = "file")]
+            Error::File(msg) => msg,
+            #[cfg(feature = "secretsmanager")]
+            Error::SecretsManager(e) => e.description(),
         }
     }
 
     fn cause(&self) -> Option<&StdError> {
         match self {
             Error::Store(e) => e.cause(),
-            Error::Envy(e) => e.cause(),
+            Error::Parameters(e) => e.cause(),
+            Error::MissingParameters(_) => None,
+            Error::Deserialize(_)
+            | Error::Aggregate(_)
+            | Error::Timeout(_)
+            | Error::Runtime(_)
+            | Error::InvalidPath(..)
+            | Error::Collision(..)
+            | Error::Serialize(_) => None,
+            Error::Put(e) => e.cause(),
+            #[cfg(feature = "file")]
+            Error::File(_) => None,
+            #[cfg(feature = "secretsmanager")]
+            Error::SecretsManager(e) => e.cause(),
         }
     }
 }
@@ -49,7 +116,194 @@ impl fmt::Display for Error {
     ) -> fmt::Result {
         match self {
             Error::Store(e) => write!(fmt, "{}", e),
-            Error::Envy(e) => write!(fmt, "{}", e),
+            Error::Parameters(e) => write!(fmt, "{}", e),
+            Error::MissingParameters(names) => {
+                write!(fmt, "missing parameters {}", names.join(", "))
+            }
+            Error::Deserialize(e) => write!(fmt, "{}", e),
+            Error::Aggregate(errors) => {
+                write!(fmt, "failed to deserialize {} values", errors.len())?;
+                for err in errors {
+                    write!(fmt, "\n  {}", err)?;
+                }
+                Ok(())
+            }
+            Error::Timeout(duration) => write!(
+                fmt,
+                "timed out resolving parameters after {}ms",
+                duration.as_millis()
+            ),
+            Error::Runtime(msg) => write!(fmt, "failed to start runtime: {}", msg),
+            Error::InvalidPath(path, reason) => {
+                write!(fmt, "invalid parameter path {}: {}", path, reason)
+            }
+            Error::Collision(first, second) => write!(
+                fmt,
+                "parameters {} and {} resolve to the same key",
+                first, second
+            ),
+            Error::Serialize(msg) => write!(fmt, "failed to serialize parameters: {}", msg),
+            Error::Put(e) => write!(fmt, "{}", e),
+            #[cfg(feature = "file")]
+            Error::File(msg) => write!(fmt, "failed to read parameter file {}", msg),
+            #[cfg(feature = "secretsmanager")]
+            Error::SecretsManager(e) => write!(fmt, "{}", e),
+        }
+    }
+}
+
+/// Describes a value which is missing or could not be deserialized along with the
+/// field and parameter it was resolved for, when known
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeserializeError {
+    message: String,
+    name: Option<String>,
+    type_: Option<ParameterType>,
+    field: Option<String>,
+    missing: bool,
+    location: Option<Vec<String>>,
+}
+
+impl DeserializeError {
+    /// Returns a description of the failure
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the full name of the parameter the value was resolved from, if any.
+    /// For missing values this is the name the value was expected to be resolved from
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the type of the parameter the value was resolved from, if known
+    pub fn parameter_type(&self) -> Option<ParameterType> {
+        self.type_
+    }
+
+    /// Returns the `.` separated path of the field being deserialized, if any
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// Returns true when no value was resolved for a required field
+    pub fn is_missing(&self) -> bool {
+        self.missing
+    }
+
+    /// Returns the path segments below a prefix where the failure occurred, once known
+    pub(crate) fn location(&self) -> Option<&[String]> {
+        self.location.as_deref()
+    }
+
+    /// Attaches the parameter a failed value at `path` was resolved from
+    pub(crate) fn parameter(
+        mut self,
+        name: String,
+        type_: Option<ParameterType>,
+        path: Vec<String>,
+    ) -> Self {
+        if self.location.is_none() {
+            self.name = Some(name);
+            self.type_ = type_;
+            self.field = Some(path.join("."));
+            self.location = Some(path);
+        }
+        self
+    }
+
+    /// Replaces the message of a failure raised while deserializing a `SecureString`
+    /// value, which may quote the value, keeping only what serde expected in its place
+    pub(crate) fn redacted(mut self) -> Self {
+        if self.location.is_none() {
+            let builtin = ["invalid type: ", "invalid value: ", "unknown variant "]
+                .iter()
+                .any(|prefix| self.message.starts_with(prefix));
+            let expected = match self.message.rfind(", expected ") {
+                Some(at) if builtin => self.message[at..].to_string(),
+                _ => String::new(),
+            };
+            self.message = format!("invalid value{}", expected);
+        }
+        self
+    }
+
+    /// Qualifies the field of a failure raised within the branch at `path`
+    pub(crate) fn within(
+        mut self,
+        path: &[String],
+    ) -> Self {
+        if self.location.is_none() {
+            let mut location = path.to_vec();
+            location.extend(self.field.take());
+            if !location.is_empty() {
+                self.field = Some(location.join("."));
+            }
+            self.location = Some(location);
+        }
+        self
+    }
+
+    /// Names the parameter a missing value was expected to be resolved from
+    pub(crate) fn expected_below(
+        mut self,
+        prefix: &str,
+    ) -> Self {
+        if let (true, None, Some(location)) = (self.missing, &self.name, &self.location) {
+            self.name = Some(format!(
+                "{}/{}",
+                prefix.trim_end_matches('/'),
+                location.join("/")
+            ));
+        }
+        self
+    }
+}
+
+impl de::Error for DeserializeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        DeserializeError {
+            message: msg.to_string(),
+            name: None,
+            type_: None,
+            field: None,
+            missing: false,
+            location: None,
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeserializeError {
+            field: Some(field.into()),
+            missing: true,
+            ..<Self as de::Error>::custom("missing value")
+        }
+    }
+}
+
+impl StdError for DeserializeError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(fmt, "{}", self.message)?;
+        if let Some(field) = &self.field {
+            write!(fmt, " for field `{}`", field)?;
+        }
+        match (&self.name, self.type_) {
+            (Some(name), _) if self.missing => write!(fmt, ", expected parameter {}", name),
+            (Some(name), Some(type_)) => write!(fmt, " from {} parameter {}", type_, name),
+            (Some(name), None) => write!(fmt, " from parameter {}", name),
+            _ => Ok(()),
         }
     }
 }
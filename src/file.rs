@@ -0,0 +1,233 @@
+// Std lib
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Third party
+use futures::future;
+use serde_json::Value;
+
+// Ours
+use crate::{Error, Options, Parameter, ParameterSource, Parameters};
+
+/// The environment variable naming a file that [from_path](fn.from_path.html) and friends
+/// resolve values from in place of parameter store
+pub const FILE_ENV_VAR: &str = "ENVY_STORE_FILE";
+
+/// Resolves parameters from a local JSON, YAML or TOML file, which is useful for offline development
+///
+/// The format is determined by the file's extension: `.json`, `.yaml`, `.yml` or `.toml`.
+/// Keys mirror parameter names and objects are joined with `/`, so the following
+/// both provide the parameter `/app/prod/db/host`
+///
+/// ```yaml
+/// /app/prod/db/host: localhost
+/// ```
+///
+/// ```yaml
+/// app:
+///   prod:
+///     db:
+///       host: localhost
+/// ```
+///
+/// Arrays are resolved as comma separated lists. The file is read each time parameters are
+/// requested and parameter filters are ignored.
+///
+/// Requires the `file` feature
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    /// Creates a new source reading from the file at `path`
+    pub fn new<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        FileSource {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Creates a new source reading from the file named by the `ENVY_STORE_FILE`
+    /// environment variable, if set
+    pub fn from_env() -> Option<Self> {
+        env::var_os(FILE_ENV_VAR).map(FileSource::new)
+    }
+
+    /// Returns a source which reads from the file named by the `ENVY_STORE_FILE`
+    /// environment variable when set and otherwise from `source`
+    pub fn from_env_or<S>(source: S) -> FileOr<S>
+    where
+        S: ParameterSource,
+    {
+        FileOr {
+            file: FileSource::from_env(),
+            source,
+        }
+    }
+
+    fn read(&self) -> Result<Vec<Parameter>, Error> {
+        let error = |message: String| Error::File(format!("{}: {}", self.path.display(), message));
+        let contents = fs::read_to_string(&self.path).map_err(|e| error(e.to_string()))?;
+        let extension = self
+            .path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        parse(extension, &contents).map_err(error)
+    }
+}
+
+impl ParameterSource for FileSource {
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters {
+        match self.read() {
+            Ok(parameters) => parameters.parameters(path_prefix, options),
+            Err(err) => Box::new(future::err(err)),
+        }
+    }
+}
+
+/// A source which resolves from a [FileSource](struct.FileSource.html) when one is
+/// configured and otherwise from another source
+///
+/// Created with [FileSource::from_env_or](struct.FileSource.html#method.from_env_or)
+#[derive(Clone, Debug)]
+pub struct FileOr<S> {
+    file: Option<FileSource>,
+    source: S,
+}
+
+impl<S> ParameterSource for FileOr<S>
+where
+    S: ParameterSource,
+{
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters {
+        match &self.file {
+            Some(file) => file.parameters(path_prefix, options),
+            None => self.source.parameters(path_prefix, options),
+        }
+    }
+
+    fn validate_prefix(
+        &self,
+        path_prefix: &Path,
+    ) -> Result<String, Error> {
+        match &self.file {
+            Some(file) => file.validate_prefix(path_prefix),
+            None => self.source.validate_prefix(path_prefix),
+        }
+    }
+
+    fn parameters_named(
+        &self,
+        names: &[String],
+        options: &Options,
+    ) -> Parameters {
+        match &self.file {
+            Some(file) => file.parameters_named(names, options),
+            None => self.source.parameters_named(names, options),
+        }
+    }
+}
+
+fn parse(
+    extension: &str,
+    contents: &str,
+) -> Result<Vec<Parameter>, String> {
+    let value: Value = match extension {
+        "json" => serde_json::from_str(contents).map_err(|e| e.to_string())?,
+        "yaml" | "yml" => serde_yaml::from_str(contents).map_err(|e| e.to_string())?,
+        "toml" => toml::from_str(contents).map_err(|e| e.to_string())?,
+        other => return Err(format!("unsupported file extension '{}'", other)),
+    };
+    let mut parameters = Vec::new();
+    match value {
+        Value::Object(_) => flatten(String::new(), value, &mut parameters),
+        _ => return Err("expected a map of parameter names to values".into()),
+    }
+    Ok(parameters)
+}
+
+fn flatten(
+    name: String,
+    value: Value,
+    parameters: &mut Vec<Parameter>,
+) {
+    let value = match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                flatten(
+                    format!("{}/{}", name, key.trim_matches('/')),
+                    value,
+                    parameters,
+                );
+            }
+            return;
+        }
+        Value::Array(values) => values
+            .into_iter()
+            .map(|value| match value {
+                Value::String(value) => value,
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        Value::String(value) => value,
+        Value::Null => return,
+        other => other.to_string(),
+    };
+    parameters.push(Parameter::new(name, value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::Parameter;
+
+    #[test]
+    fn parses_flat_and_nested_names() {
+        let expected = Ok(vec![
+            Parameter::new("/app/prod/db/host", "localhost"),
+            Parameter::new("/app/prod/db/port", "5432"),
+            Parameter::new("/app/prod/hosts", "a,b"),
+        ]);
+        assert_eq!(
+            expected,
+            parse(
+                "json",
+                r#"{"/app/prod/db": {"host": "localhost", "port": 5432}, "/app/prod/hosts": ["a", "b"]}"#
+            )
+        );
+        assert_eq!(
+            expected,
+            parse(
+                "yaml",
+                "app:\n  prod:\n    db:\n      host: localhost\n      port: 5432\n    hosts: [a, b]\n"
+            )
+        );
+        assert_eq!(
+            expected,
+            parse(
+                "toml",
+                "[app.prod]\nhosts = [\"a\", \"b\"]\n\n[app.prod.db]\nhost = \"localhost\"\nport = 5432\n"
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_extensions() {
+        assert!(parse("ini", "").is_err())
+    }
+}
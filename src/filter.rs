@@ -0,0 +1,198 @@
+// Std lib
+use std::{fmt, str::FromStr};
+
+// Third party
+use rusoto_ssm::ParameterStringFilter;
+
+/// The type of a parameter store value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterType {
+    /// A plain text value
+    String,
+    /// A comma separated list of plain text values
+    StringList,
+    /// A value encrypted with a KMS key
+    SecureString,
+}
+
+impl ParameterType {
+    /// Returns the name parameter store uses for this type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParameterType::String => "String",
+            ParameterType::StringList => "StringList",
+            ParameterType::SecureString => "SecureString",
+        }
+    }
+}
+
+impl fmt::Display for ParameterType {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ParameterType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "String" => Ok(ParameterType::String),
+            "StringList" => Ok(ParameterType::StringList),
+            "SecureString" => Ok(ParameterType::SecureString),
+            other => Err(format!("unknown parameter type {}", other)),
+        }
+    }
+}
+
+/// Pins a parameter to a specific version, or to the version carrying a label
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{EnvyStore, Selector};
+/// use std::collections::HashMap;
+///
+/// let config = EnvyStore::builder()
+///   .selector(Selector::label("release-42"))
+///   .key_selector("db/password", Selector::Version(3))
+///   .load::<HashMap<String, String>, _>("/demo");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Selector {
+    /// Selects a version by number
+    Version(i64),
+    /// Selects the version carrying a label
+    Label(String),
+}
+
+impl Selector {
+    /// Selects the version carrying `label`
+    pub fn label<L>(label: L) -> Self
+    where
+        L: Into<String>,
+    {
+        Selector::Label(label.into())
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            Selector::Version(version) => write!(fmt, "{}", version),
+            Selector::Label(label) => write!(fmt, "{}", label),
+        }
+    }
+}
+
+/// Narrows the parameters resolved under a path prefix
+///
+/// Filters are applied by parameter store itself. When more than one filter is provided,
+/// a parameter must match all of them to be resolved
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{EnvyStore, Filter, ParameterType};
+/// use std::collections::HashMap;
+///
+/// let config = EnvyStore::builder()
+///   .filter(Filter::tag("service", "billing"))
+///   .filter(Filter::types(vec![ParameterType::String, ParameterType::SecureString]))
+///   .load::<HashMap<String, String>, _>("/demo");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    /// Matches parameters with a tag `key` whose value is any of the given values
+    Tag(String, Vec<String>),
+    /// Matches parameters of any of the given types
+    Type(Vec<ParameterType>),
+    /// Matches parameters with a version carrying any of the given labels
+    Label(Vec<String>),
+}
+
+impl Filter {
+    /// Matches parameters with a tag `key` set to `value`
+    pub fn tag<K, V>(
+        key: K,
+        value: V,
+    ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Filter::Tag(key.into(), vec![value.into()])
+    }
+
+    /// Matches parameters of any of the given types
+    pub fn types<T>(types: T) -> Self
+    where
+        T: IntoIterator<Item = ParameterType>,
+    {
+        Filter::Type(types.into_iter().collect())
+    }
+
+    /// Matches parameters with a version labeled `label`
+    pub fn label<L>(label: L) -> Self
+    where
+        L: Into<String>,
+    {
+        Filter::Label(vec![label.into()])
+    }
+}
+
+impl From<Filter> for ParameterStringFilter {
+    fn from(filter: Filter) -> Self {
+        let (key, values) = match filter {
+            Filter::Tag(key, values) => (format!("tag:{}", key), values),
+            Filter::Type(types) => (
+                "Type".into(),
+                types.iter().map(|t| t.as_str().to_string()).collect(),
+            ),
+            Filter::Label(labels) => ("Label".into(), labels),
+        };
+        ParameterStringFilter {
+            key,
+            option: Some("Equals".into()),
+            values: Some(values),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, ParameterType};
+    use rusoto_ssm::ParameterStringFilter;
+
+    #[test]
+    fn converts_filters_to_parameter_string_filters() {
+        assert_eq!(
+            ParameterStringFilter {
+                key: "tag:service".into(),
+                option: Some("Equals".into()),
+                values: Some(vec!["billing".into()]),
+            },
+            Filter::tag("service", "billing").into()
+        );
+        assert_eq!(
+            ParameterStringFilter {
+                key: "Type".into(),
+                option: Some("Equals".into()),
+                values: Some(vec!["String".into(), "SecureString".into()]),
+            },
+            Filter::types(vec![ParameterType::String, ParameterType::SecureString]).into()
+        );
+    }
+
+    #[test]
+    fn parses_parameter_types() {
+        assert_eq!(Ok(ParameterType::StringList), "StringList".parse());
+        assert!("Strings".parse::<ParameterType>().is_err());
+    }
+}
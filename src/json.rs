@@ -0,0 +1,117 @@
+// Third party
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer,
+};
+
+/// Deserializes a field from a parameter whose value is a JSON document
+///
+/// Intended for use with serde's `deserialize_with` field attribute. See
+/// [Builder::json_key](struct.Builder.html#method.json_key) for decoding
+/// values without annotating fields.
+///
+/// Requires the `json` feature
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use std::collections::HashMap;
+///
+/// #[derive(Deserialize)]
+/// struct Route {
+///   upstream: String,
+///   weight: u8,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///   // resolved from /demo/routes holding
+///   // {"/api":{"upstream":"api.internal","weight":100}}
+///   #[serde(deserialize_with = "envy_store::json")]
+///   routes: HashMap<String, Route>,
+/// }
+///
+/// let config = envy_store::from_path::<Config, _>("/demo");
+/// ```
+pub fn json<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = String::deserialize(deserializer)?;
+    serde_json::from_str(&value)
+        .map_err(|e| de::Error::custom(format_args!("{} while parsing JSON value", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{de::Settings, deserialize, entries, Parameter};
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Route {
+        upstream: String,
+        weight: u8,
+    }
+
+    #[test]
+    fn deserializes_annotated_fields() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            #[serde(deserialize_with = "crate::json")]
+            routes: HashMap<String, Route>,
+        }
+        let parameters = vec![Parameter::new(
+            "/test/routes",
+            r#"{"/api":{"upstream":"api.internal","weight":100}}"#,
+        )];
+        assert_eq!(
+            Ok(Config {
+                routes: hashmap!("/api".to_string() => Route {
+                    upstream: "api.internal".into(),
+                    weight: 100,
+                }),
+            }),
+            deserialize(
+                Some("/test"),
+                &Settings::default(),
+                entries("/test", parameters)
+            )
+        )
+    }
+
+    #[test]
+    fn deserializes_json_keys() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            routes: Vec<Route>,
+            flags: HashMap<String, bool>,
+            name: String,
+        }
+        let parameters = vec![
+            Parameter::new(
+                "/test/routes",
+                r#"[{"upstream":"api.internal","weight":100}]"#,
+            ),
+            Parameter::new("/test/flags", r#"{"beta":true}"#),
+            Parameter::new("/test/name", "app"),
+        ];
+        let settings = Settings {
+            json_keys: vec![vec!["routes".into()], vec!["flags".into()]],
+            ..Settings::default()
+        };
+        assert_eq!(
+            Ok(Config {
+                routes: vec![Route {
+                    upstream: "api.internal".into(),
+                    weight: 100,
+                }],
+                flags: hashmap!("beta".to_string() => true),
+                name: "app".into(),
+            }),
+            deserialize(Some("/test"), &settings, entries("/test", parameters))
+        )
+    }
+}
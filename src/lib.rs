@@ -10,6 +10,9 @@
 //! This leads to better clarity on what application a set of parameters belong to as well as enables
 //! the paths based query API which has performance benefits and is the recommended best practice by AWS.
 //!
+//! Path segments below the prefix are treated as levels of nesting, so a parameter named
+//! `/demo/db/host` resolved with the prefix `/demo` fills the `host` field of a `db` field's struct.
+//!
 //! This crate assumes the use of the [AWS default credential chain](https://docs.aws.amazon.com/cli/latest/userguide/cli-chap-getting-started.html) for authenticating requests
 //! with AWS. Don't worry, if you've used any AWS tooling in the past, you likely already have this configured.
 //! You will also need to ensure these credentials have the `ssm:GetParametersByPath` [IAM permission](https://docs.aws.amazon.com/systems-manager/latest/userguide/sysman-paramstore-access.html).
@@ -42,120 +45,349 @@
 #[macro_use]
 extern crate maplit;
 
+mod cache;
+mod de;
+mod env;
 mod error;
+#[cfg(feature = "file")]
+mod file;
+mod filter;
+#[cfg(feature = "json")]
+mod json;
+mod normalize;
+mod path;
+mod plan;
+mod secret;
+#[cfg(feature = "secretsmanager")]
+mod secretsmanager;
+mod ser;
+mod source;
+mod ssm;
+mod store;
+mod watch;
 
 // Std lib
-use futures::{stream, Future, Stream};
-use rusoto_ssm::{GetParametersByPathRequest, Parameter, Ssm, SsmClient};
-use serde::de::DeserializeOwned;
-use std::{collections::HashMap, path::Path};
+use futures::future;
+use futures::Future;
+use rusoto_ssm::{Ssm, SsmClient};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{path::Path, time::Duration};
 
 // Ours
 
-pub use crate::error::Error;
+use crate::de::{Entry, Node, Settings};
+#[cfg(feature = "file")]
+pub use crate::file::{FileOr, FileSource, FILE_ENV_VAR};
+#[cfg(feature = "json")]
+pub use crate::json::json;
+#[cfg(feature = "secretsmanager")]
+pub use crate::secretsmanager::{SecretsManagerError, SecretsManagerSource};
+pub use crate::{
+    cache::CachedSource,
+    env::EnvOverlay,
+    error::{DeserializeError, Error},
+    filter::{Filter, ParameterType, Selector},
+    normalize::{Case, Normalize},
+    path::ParameterPath,
+    plan::{Difference, Plan},
+    secret::Secret,
+    ser::secure,
+    source::{Options, Parameter, ParameterSource, Parameters},
+    ssm::SsmSource,
+    store::{Builder, EnvyStore},
+    watch::{Subscription, Watched},
+};
 
 /// Resolves parameter store values and deserialize them into
 /// a typesafe struct
 ///
 /// `path_prefix` is assumed to be the path prefixed, e.g `/sweet-app/prod`.
 /// Parameter store value names are then expected be of the form `/sweet-app/prod/db-pass`
-/// `/sweet-app/prod/db-username`, and so forth.
+/// `/sweet-app/prod/db-username`, and so forth. Deeper names such as `/sweet-app/prod/db/pass`
+/// resolve to nested structs or maps.
+///
+/// Prefixes which are not valid parameter store paths fail with `Error::InvalidPath`.
+/// See [ParameterPath](struct.ParameterPath.html)
+///
+/// With the `file` feature enabled, values are resolved from the file named by the
+/// `ENVY_STORE_FILE` environment variable instead, when set. See [FileSource](struct.FileSource.html)
 pub fn from_path<T, P>(path_prefix: P) -> impl Future<Item = T, Error = Error> + Send
 where
     T: DeserializeOwned + Send,
     P: AsRef<Path>,
 {
-    crate::from_client(SsmClient::new(Default::default()), path_prefix)
+    default_builder().load(path_prefix)
 }
 
 /// Resolves parameter store values and deserializes them into
 /// a typesafe struct. Similar to [from_path](fn.from_path.html) but
 /// also accepts a customized `rusoto_ssm::Ssm`
 /// implementation
+///
+/// See [EnvyStore](struct.EnvyStore.html) for control over other request settings
 pub fn from_client<T, C, P>(
     client: C,
     path_prefix: P,
 ) -> impl Future<Item = T, Error = Error> + Send
 where
     T: DeserializeOwned + Send,
-    C: Ssm + Send,
+    C: Ssm + Send + Sync + 'static,
     P: AsRef<Path>,
 {
-    enum PageState {
-        Start(Option<String>),
-        Next(String),
-        End,
-    }
-    let prefix = path_prefix
-        .as_ref()
-        .to_str()
-        .unwrap_or_default()
-        .to_string();
-    let prefix_strip = prefix.len() + 1;
-    stream::unfold(PageState::Start(None), move |state| {
-        let next_token = match state {
-            PageState::Start(start) => start,
-            PageState::Next(next) => Some(next),
-            PageState::End => return None,
-        };
-        Some(
-            client
-                .get_parameters_by_path(GetParametersByPathRequest {
-                    next_token,
-                    path: prefix.clone(),
-                    with_decryption: Some(true),
-                    recursive: Some(true),
-                    ..GetParametersByPathRequest::default()
-                })
-                .map_err(Error::from)
-                .map(move |resp| {
-                    let next_state = match resp.next_token {
-                        Some(next) => {
-                            if next.is_empty() {
-                                PageState::End
-                            } else {
-                                PageState::Next(next)
-                            }
-                        }
-                        _ => PageState::End,
-                    };
-                    (
-                        stream::iter_ok(resp.parameters.unwrap_or_default()),
-                        next_state,
-                    )
-                }),
-        )
-    })
-    .flatten()
-    .collect()
-    .and_then(move |parameters| deserialize(prefix_strip, parameters))
+    EnvyStore::builder().client(client).load(path_prefix)
 }
 
-fn deserialize<T>(
-    prefix_strip: usize,
+/// Resolves parameter store values and deserializes them into
+/// a typesafe struct, blocking the current thread until done. Similar to
+/// [from_path](fn.from_path.html) but does not require a runtime to drive a returned future
+///
+/// When a `timeout` is provided and loading takes longer, `Error::Timeout` is returned.
+/// This panics when called from within a tokio runtime, where
+/// [from_path](fn.from_path.html) should be used instead
+///
+/// ```no_run
+/// # use serde::Deserialize;
+/// # use std::time::Duration;
+/// # #[derive(Deserialize)]
+/// # struct Config {
+/// #   foo: String,
+/// # }
+/// let config = envy_store::from_path_blocking::<Config, _>(
+///    "/demo",
+///    Some(Duration::from_secs(5))
+/// );
+/// ```
+pub fn from_path_blocking<T, P>(
+    path_prefix: P,
+    timeout: Option<Duration>,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned + Send,
+    P: AsRef<Path>,
+{
+    default_builder().load_blocking(path_prefix, timeout)
+}
+
+/// Resolves parameter store values and deserializes them into
+/// a typesafe struct. Similar to [from_path](fn.from_path.html) but
+/// returns a `std::future::Future` suitable for use with `async`/`await`
+///
+/// Requires the `async` feature. Requests are made with rusoto's tokio 0.1 based client on
+/// a background thread, so the future may be awaited from any executor
+///
+/// ```no_run
+/// # use serde::Deserialize;
+/// # #[derive(Deserialize)]
+/// # struct Config {
+/// #   foo: String,
+/// # }
+/// async fn config() -> Result<Config, envy_store::Error> {
+///    envy_store::from_path_async("/demo").await
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub fn from_path_async<T, P>(
+    path_prefix: P
+) -> impl std::future::Future<Output = Result<T, Error>> + Send
+where
+    T: DeserializeOwned + Send + 'static,
+    P: AsRef<Path>,
+{
+    default_builder().load_async(path_prefix)
+}
+
+/// Resolves parameter store values and deserializes them into
+/// a typesafe struct. Similar to [from_client](fn.from_client.html) but
+/// returns a `std::future::Future` suitable for use with `async`/`await`
+///
+/// Requires the `async` feature. See [from_path_async](fn.from_path_async.html)
+#[cfg(feature = "async")]
+pub fn from_client_async<T, C, P>(
+    client: C,
+    path_prefix: P,
+) -> impl std::future::Future<Output = Result<T, Error>> + Send
+where
+    T: DeserializeOwned + Send + 'static,
+    C: Ssm + Send + Sync + 'static,
+    P: AsRef<Path>,
+{
+    EnvyStore::builder().client(client).load_async(path_prefix)
+}
+
+/// Serializes a typesafe struct into parameter store values named below `path_prefix`,
+/// overwriting any which already exist
+///
+/// This is the inverse of [from_path](fn.from_path.html). Fields become parameters named
+/// by their path below `path_prefix`, with nested structs and maps adding further segments.
+/// Sequences are written as `StringList` parameters, [Secret](struct.Secret.html) values and
+/// fields marked with [secure](fn.secure.html) as `SecureString` parameters and all other
+/// values as `String` parameters. `None` values are skipped. Empty values and sequences
+/// fail with `Error::Serialize` and names which are not valid
+/// [ParameterPath](struct.ParameterPath.html)s fail with `Error::InvalidPath`, both before
+/// any parameter is written.
+///
+/// This requires the `ssm:PutParameter` IAM permission
+///
+/// ```no_run
+/// use envy_store::Secret;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///   foo: Secret<String>,
+///   bar: Vec<String>,
+///   zar: u32,
+/// }
+///
+/// let config = Config {
+///   foo: Secret::new("bar".into()),
+///   bar: vec!["baz".into(), "boom".into()],
+///   zar: 42,
+/// };
+/// // Returns a `Future` which resolves once every parameter is written
+/// let written = envy_store::to_path("/demo", &config);
+/// ```
+pub fn to_path<T, P>(
+    path_prefix: P,
+    value: &T,
+) -> impl Future<Item = (), Error = Error> + Send
+where
+    T: Serialize + ?Sized,
+    P: AsRef<Path>,
+{
+    to_client(SsmClient::new(Default::default()), path_prefix, value)
+}
+
+/// Serializes a typesafe struct into parameter store values named below `path_prefix`.
+/// Similar to [to_path](fn.to_path.html) but also accepts a customized `rusoto_ssm::Ssm`
+/// implementation
+pub fn to_client<T, C, P>(
+    client: C,
+    path_prefix: P,
+    value: &T,
+) -> impl Future<Item = (), Error = Error> + Send
+where
+    T: Serialize + ?Sized,
+    C: Ssm + Send + Sync + 'static,
+    P: AsRef<Path>,
+{
+    future::result(
+        ParameterPath::new(path_prefix)
+            .and_then(|prefix| path::writable(prefix.as_str()).map(|_| prefix))
+            .and_then(|prefix| ser::parameters(prefix.as_str(), value).map_err(Error::Serialize)),
+    )
+    .and_then(move |parameters| ssm::put(client, parameters))
+}
+
+/// Returns a builder for the source used by `from_path` and friends
+#[cfg(not(feature = "file"))]
+fn default_builder() -> Builder<SsmSource> {
+    EnvyStore::builder()
+}
+
+/// Returns a builder for the source used by `from_path` and friends
+#[cfg(feature = "file")]
+fn default_builder() -> Builder<FileOr<SsmSource>> {
+    EnvyStore::builder().source(FileSource::from_env_or(SsmSource::default()))
+}
+
+/// Converts parameters resolved under `prefix` into entries whose paths
+/// are their names relative to `prefix`, skipping any not nested below it
+fn entries(
+    prefix: &str,
     parameters: Vec<Parameter>,
+) -> Vec<Entry> {
+    parameters
+        .into_iter()
+        .filter_map(|param| {
+            let path = path::relative(prefix, &param.name)?
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_string)
+                .collect();
+            Some(Entry {
+                path,
+                name: param.name,
+                value: param.value,
+                type_: param.type_,
+            })
+        })
+        .collect()
+}
+
+/// The most attempts made to deserialize values around those which failed
+const MAX_ATTEMPTS: usize = 256;
+
+/// Deserializes entries in order. Values of later entries
+/// replace those of earlier entries with the same path
+///
+/// When a value is missing or fails to deserialize, a placeholder is put in its
+/// place and deserialization is attempted again so that every failure is reported
+/// at once. Missing values are reported as expected below `expected_prefix`, when
+/// there is one
+fn deserialize<T>(
+    expected_prefix: Option<&str>,
+    settings: &Settings,
+    entries: Vec<Entry>,
 ) -> Result<T, Error>
 where
     T: DeserializeOwned + Send,
 {
-    envy::from_iter::<_, T>(parameters.into_iter().fold(
-        HashMap::new(),
-        |mut result: HashMap<String, String>, param| {
-            if let (Some(name), Some(value)) = (param.name, param.value) {
-                result.insert(name[prefix_strip..].to_string(), value);
+    let mut root = Node::default();
+    for entry in entries {
+        root.insert(entry, settings)?;
+    }
+    let mut errors = Vec::new();
+    let mut patched = Vec::new();
+    for _ in 0..MAX_ATTEMPTS {
+        let err = match T::deserialize(root.clone()) {
+            Ok(value) if errors.is_empty() => return Ok(value),
+            Ok(_) => break,
+            Err(err) => match expected_prefix {
+                Some(prefix) => err.expected_below(prefix),
+                None => err,
+            },
+        };
+        let location = match err.location() {
+            // a placeholder failed in turn, which was reported for the value it replaced
+            Some(location) if patched.contains(&location.to_vec()) => break,
+            // only values which are missing or failed to parse are replaced. Replacing a
+            // branch which failed as a whole, e.g. with an unknown field, would report
+            // every value below it as missing
+            Some(location) if err.is_missing() || err.name().is_some() => location.to_vec(),
+            _ => {
+                errors.push(err);
+                break;
             }
-            result
-        },
-    ))
-    .map_err(Error::from)
+        };
+        // a missing branch is reported through the values missing below it rather than as
+        // a parameter of its own
+        if err.is_missing() {
+            errors.retain(|earlier: &DeserializeError| {
+                !earlier.is_missing()
+                    || !earlier.location().map_or(false, |parent| {
+                        parent.len() < location.len() && location.starts_with(parent)
+                    })
+            });
+        }
+        root.placeholder(&location);
+        patched.push(location);
+        errors.push(err);
+    }
+    match errors.len() {
+        1 => Err(Error::Deserialize(errors.remove(0))),
+        _ => Err(Error::Aggregate(errors)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{deserialize, from_client};
+    use super::{deserialize, entries, from_client, Settings};
+    use crate::{Error, Parameter, ParameterType};
     use futures::Future;
     use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
-    use rusoto_ssm::{Parameter, SsmClient};
+    use rusoto_ssm::SsmClient;
+    use serde::Deserialize;
     use std::collections::HashMap;
 
     #[test]
@@ -188,14 +420,334 @@ mod tests {
 
     #[test]
     fn deserializes_with_expected_parameters() {
-        let parameters = vec![Parameter {
-            name: Some("/test/foo".into()),
-            value: Some("bar".into()),
-            ..Parameter::default()
-        }];
+        let parameters = vec![Parameter::new("/test/foo", "bar")];
         assert_eq!(
             Ok(hashmap!("foo".to_string() => "bar".to_string())),
-            deserialize(6, parameters)
+            deserialize(
+                Some("/test"),
+                &Settings::default(),
+                entries("/test", parameters)
+            )
+        )
+    }
+
+    #[test]
+    fn deserializes_nested_parameters() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Db {
+            host: String,
+            port: u16,
+        }
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            name: String,
+            db: Db,
+            tags: HashMap<String, String>,
+        }
+        let parameters = vec![
+            ("/test/name", "app"),
+            ("/test/db/host", "localhost"),
+            ("/test/db/port", "5432"),
+            ("/test/tags/team", "platform"),
+        ]
+        .into_iter()
+        .map(|(name, value)| Parameter::new(name, value))
+        .collect();
+        assert_eq!(
+            Ok(Config {
+                name: "app".into(),
+                db: Db {
+                    host: "localhost".into(),
+                    port: 5432,
+                },
+                tags: hashmap!("team".to_string() => "platform".to_string()),
+            }),
+            deserialize(
+                Some("/test"),
+                &Settings::default(),
+                entries("/test", parameters)
+            )
+        )
+    }
+
+    #[test]
+    fn fails_when_value_and_nested_parameters_collide() {
+        let parameters = vec![("/test/db", "x"), ("/test/db/host", "localhost")]
+            .into_iter()
+            .map(|(name, value)| Parameter::new(name, value))
+            .collect();
+        assert!(deserialize::<HashMap<String, HashMap<String, String>>>(
+            Some("/test"),
+            &Settings::default(),
+            entries("/test", parameters)
+        )
+        .is_err())
+    }
+
+    #[test]
+    fn names_parameter_and_field_in_errors() {
+        #[allow(dead_code)]
+        #[derive(Deserialize, Debug)]
+        struct Db {
+            host: String,
+            port: u16,
+        }
+        #[allow(dead_code)]
+        #[derive(Deserialize, Debug)]
+        struct Config {
+            db: Db,
+        }
+        let parameters = vec![
+            Parameter::new("/test/db/host", "localhost"),
+            Parameter::typed("/test/db/port", "abc", ParameterType::String),
+        ];
+        match deserialize::<Config>(
+            Some("/test"),
+            &Settings::default(),
+            entries("/test", parameters),
+        ) {
+            Err(Error::Deserialize(err)) => {
+                assert_eq!(Some("/test/db/port"), err.name());
+                assert_eq!(Some(ParameterType::String), err.parameter_type());
+                assert_eq!(Some("db.port"), err.field());
+                assert_eq!(
+                    "invalid digit found in string while parsing value 'abc' for field `db.port` from String parameter /test/db/port",
+                    err.to_string()
+                );
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        match deserialize::<Config>(
+            Some("/test"),
+            &Settings::default(),
+            entries("/test", vec![Parameter::new("/test/db/port", "5432")]),
+        ) {
+            Err(Error::Deserialize(err)) => {
+                assert!(err.is_missing());
+                assert_eq!(Some("/test/db/host"), err.name());
+                assert_eq!(
+                    "missing value for field `db.host`, expected parameter /test/db/host",
+                    err.to_string()
+                );
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_branch_failures_without_placeholders() {
+        #[allow(dead_code)]
+        #[derive(Deserialize, Debug)]
+        #[serde(deny_unknown_fields)]
+        struct Db {
+            host: String,
+            port: u16,
+        }
+        #[allow(dead_code)]
+        #[derive(Deserialize, Debug)]
+        #[serde(deny_unknown_fields)]
+        struct Config {
+            db: Db,
+        }
+        let parameters = vec![
+            Parameter::new("/test/db/host", "localhost"),
+            Parameter::new("/test/db/port", "5432"),
+            Parameter::new("/test/db/stray", "value"),
+        ];
+        match deserialize::<Config>(
+            Some("/test"),
+            &Settings::default(),
+            entries("/test", parameters),
+        ) {
+            Err(Error::Deserialize(err)) => {
+                assert!(!err.is_missing());
+                assert!(err.message().starts_with("unknown field `stray`"));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_all_missing_and_invalid_values() {
+        #[allow(dead_code)]
+        #[derive(Deserialize, Debug)]
+        struct Db {
+            host: String,
+            port: u16,
+        }
+        #[allow(dead_code)]
+        #[derive(Deserialize, Debug)]
+        struct Config {
+            name: String,
+            db: Db,
+            replicas: u8,
+        }
+        let parameters = vec![
+            Parameter::new("/test/db/port", "abc"),
+            Parameter::new("/test/replicas", "-1"),
+        ];
+        match deserialize::<Config>(
+            Some("/test"),
+            &Settings::default(),
+            entries("/test", parameters),
+        ) {
+            Err(Error::Aggregate(errors)) => {
+                let mut reported = errors
+                    .iter()
+                    .map(|err| (err.field(), err.name(), err.is_missing()))
+                    .collect::<Vec<_>>();
+                reported.sort();
+                assert_eq!(
+                    vec![
+                        (Some("db.host"), Some("/test/db/host"), true),
+                        (Some("db.port"), Some("/test/db/port"), false),
+                        (Some("name"), Some("/test/name"), true),
+                        (Some("replicas"), Some("/test/replicas"), false),
+                    ],
+                    reported
+                );
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_values_missing_below_missing_branches() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Db {
+            host: String,
+            port: u16,
+        }
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Config {
+            db: Db,
+            name: String,
+        }
+        let parameters = vec![Parameter::new("/test/name", "app")];
+        match deserialize::<Config>(
+            Some("/test"),
+            &Settings::default(),
+            entries("/test", parameters),
+        ) {
+            Err(Error::Aggregate(errors)) => {
+                let reported = errors
+                    .iter()
+                    .map(|err| (err.field(), err.name(), err.is_missing()))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    vec![
+                        (Some("db.host"), Some("/test/db/host"), true),
+                        (Some("db.port"), Some("/test/db/port"), true),
+                    ],
+                    reported
+                );
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn splits_lists_by_parameter_type() {
+        let parameter = |type_, value| Parameter {
+            type_,
+            ..Parameter::new("/test/hosts", value)
+        };
+        let hosts = |parameter, strict| {
+            deserialize::<HashMap<String, Vec<String>>>(
+                Some("/test"),
+                &Settings {
+                    strict,
+                    ..Settings::default()
+                },
+                entries("/test", vec![parameter]),
+            )
+            .map(|config| config["hosts"].clone())
+        };
+        assert_eq!(
+            Ok(vec!["a".to_string(), "b".to_string()]),
+            hosts(parameter(Some(ParameterType::StringList), "a,b"), true)
+        );
+        assert_eq!(
+            Ok(vec!["a,b".to_string()]),
+            hosts(parameter(Some(ParameterType::String), "a,b"), false)
+        );
+        assert_eq!(
+            Ok(vec!["a".to_string(), "b".to_string()]),
+            hosts(parameter(None, "a,b"), true)
+        );
+        assert!(hosts(parameter(Some(ParameterType::SecureString), "a,b"), true).is_err());
+    }
+
+    #[test]
+    fn redacts_secure_values_from_errors() {
+        #[derive(Deserialize, Debug)]
+        enum Mode {
+            Fast,
+        }
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Config {
+            port: Option<u16>,
+            mode: Option<Mode>,
+            initial: Option<char>,
+            pair: Option<(String, String)>,
+        }
+        let message = |name: &str| {
+            let parameter = Parameter::typed(
+                format!("/test/{}", name),
+                "hunter2",
+                ParameterType::SecureString,
+            );
+            match deserialize::<Config>(
+                Some("/test"),
+                &Settings::default(),
+                entries("/test", vec![parameter]),
+            ) {
+                Err(err) => err.to_string(),
+                Ok(config) => panic!("unexpected config {:?}", config),
+            }
+        };
+        assert_eq!(
+            "invalid digit found in string for field `port` from SecureString parameter /test/port",
+            message("port")
+        );
+        for name in &["mode", "initial", "pair"] {
+            let message = message(name);
+            assert!(message.starts_with("invalid value"), "{}", message);
+            assert!(!message.contains("hunter2"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn later_layers_override_earlier_layers() {
+        let layer = |prefix, parameters: Vec<(&str, &str)>| {
+            entries(
+                prefix,
+                parameters
+                    .into_iter()
+                    .map(|(name, value)| Parameter::new(name, value))
+                    .collect(),
+            )
+        };
+        let mut layers = layer(
+            "/shared",
+            vec![
+                ("/shared/region", "us-east-1"),
+                ("/shared/host", "localhost"),
+            ],
+        );
+        layers.extend(layer(
+            "/app/prod",
+            vec![("/app/prod/host", "prod.example.com")],
+        ));
+        assert_eq!(
+            Ok(hashmap!(
+                "region".to_string() => "us-east-1".to_string(),
+                "host".to_string() => "prod.example.com".to_string()
+            )),
+            deserialize(Some("/app/prod"), &Settings::default(), layers)
         )
     }
 }
@@ -0,0 +1,179 @@
+// Std lib
+use std::collections::HashMap;
+
+// Ours
+use crate::{de::Entry, Error};
+
+/// How the case of parameter names is folded before they are matched with fields
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Case {
+    /// Lowercases names, e.g. `DB-Host` becomes `db-host`
+    Lower,
+    /// Uppercases names, e.g. `db-host` becomes `DB-HOST`
+    Upper,
+    /// Leaves names as they are
+    Preserve,
+}
+
+/// Describes how parameter names, relative to the path prefix, are converted into the
+/// keys matched with fields
+///
+/// By default names are lowercased and each `/` separated segment is a level of nesting
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{EnvyStore, Normalize};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///   db_host: String,
+/// }
+///
+/// // resolves db_host from /demo/DB-Host, /demo/db.host or /demo/db/host
+/// let config = EnvyStore::builder()
+///   .normalize(Normalize::default().underscores(true).join('_'))
+///   .load::<Config, _>("/demo");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalize {
+    case: Case,
+    underscores: bool,
+    join: Option<char>,
+}
+
+impl Default for Normalize {
+    fn default() -> Self {
+        Normalize {
+            case: Case::Lower,
+            underscores: false,
+            join: None,
+        }
+    }
+}
+
+impl Normalize {
+    /// Sets how the case of names is folded. Defaults to `Case::Lower`
+    pub fn case(
+        mut self,
+        case: Case,
+    ) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Sets whether `-` and `.` in names are replaced with `_`, matching `snake_case`
+    /// field names. Defaults to `false`
+    pub fn underscores(
+        mut self,
+        underscores: bool,
+    ) -> Self {
+        self.underscores = underscores;
+        self
+    }
+
+    /// Joins the segments of nested names with `join` into a single key rather than
+    /// treating each as a level of nesting, e.g. with `_`, `db/host` becomes `db_host`
+    pub fn join(
+        mut self,
+        join: char,
+    ) -> Self {
+        self.join = Some(join);
+        self
+    }
+
+    /// Converts the segments of a name into a key
+    pub(crate) fn key(
+        &self,
+        path: &[String],
+    ) -> Vec<String> {
+        let segments = path.iter().map(|segment| {
+            let segment = match self.case {
+                Case::Lower => segment.to_lowercase(),
+                Case::Upper => segment.to_uppercase(),
+                Case::Preserve => segment.clone(),
+            };
+            if self.underscores {
+                segment.replace(&['-', '.'][..], "_")
+            } else {
+                segment
+            }
+        });
+        match self.join {
+            Some(join) => vec![segments.collect::<Vec<_>>().join(&join.to_string())],
+            None => segments.collect(),
+        }
+    }
+
+    /// Fails when the names of two entries resolve to the same key
+    pub(crate) fn distinct(
+        &self,
+        entries: &[Entry],
+    ) -> Result<(), Error> {
+        let mut keys = HashMap::new();
+        for entry in entries {
+            if let Some(other) = keys.insert(self.key(&entry.path), &entry.name) {
+                if other != &entry.name {
+                    return Err(Error::Collision(other.clone(), entry.name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Case, Normalize};
+    use crate::{de::Entry, Error};
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_keys() {
+        assert_eq!(
+            path(&["db-host", "name"]),
+            Normalize::default().key(&path(&["DB-Host", "Name"]))
+        );
+        assert_eq!(
+            path(&["DB_HOST"]),
+            Normalize::default()
+                .case(Case::Upper)
+                .underscores(true)
+                .join('_')
+                .key(&path(&["db", "host"]))
+        );
+        assert_eq!(
+            path(&["Db_Host"]),
+            Normalize::default()
+                .case(Case::Preserve)
+                .underscores(true)
+                .key(&path(&["Db.Host"]))
+        );
+    }
+
+    #[test]
+    fn detects_collisions() {
+        let entry = |name: &str, segments: &[&str]| Entry {
+            name: name.into(),
+            path: path(segments),
+            value: "value".into(),
+            type_: None,
+        };
+        let entries = vec![
+            entry("/app/db-host", &["db-host"]),
+            entry("/app/db_host", &["db_host"]),
+        ];
+        assert_eq!(Ok(()), Normalize::default().distinct(&entries));
+        assert_eq!(
+            Err(Error::Collision(
+                "/app/db-host".into(),
+                "/app/db_host".into()
+            )),
+            Normalize::default().underscores(true).distinct(&entries)
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Std lib
+use std::{fmt, path::Path, str::FromStr};
+
+// Ours
+use crate::Error;
+
+/// The most characters parameter store accepts in a fully qualified name
+const MAX_LENGTH: usize = 1011;
+
+/// The most levels parameter store accepts in a hierarchy
+const MAX_DEPTH: usize = 15;
+
+/// Roots reserved by AWS for its own parameters, compared case insensitively
+const RESERVED: &[&str] = &["aws", "ssm"];
+
+/// A validated parameter store path prefix, e.g. `/sweet-app/prod`
+///
+/// Paths must start with `/` and contain only letters, numbers and the characters
+/// `_`, `.`, `-` and `/`. They may be nested at most 15 levels deep and be at most 1011
+/// characters long. A trailing `/` is removed.
+///
+/// Paths beginning with `aws` or `ssm`, such as the public parameters under `/aws/service`,
+/// are reserved by AWS. They may be read but [to_path](fn.to_path.html) and
+/// [EnvyStore::plan](struct.EnvyStore.html#method.plan) fail with `Error::InvalidPath` for them
+///
+/// Prefixes passed to [from_path](fn.from_path.html) and friends are validated the same
+/// way, failing with `Error::InvalidPath`. Other sources may accept other prefixes, see
+/// [ParameterSource::validate_prefix](trait.ParameterSource.html#method.validate_prefix)
+///
+/// # Example
+///
+/// ```
+/// use envy_store::ParameterPath;
+///
+/// let path = ParameterPath::new("/sweet-app/prod/").unwrap();
+/// assert_eq!("/sweet-app/prod", path.as_str());
+/// assert_eq!(Some("db/host"), path.relative("/sweet-app/prod/db/host"));
+/// assert!(ParameterPath::new("sweet-app").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParameterPath(String);
+
+impl ParameterPath {
+    /// Validates and normalizes `path`
+    pub fn new<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let invalid = |reason: &str| Error::InvalidPath(path.display().to_string(), reason.into());
+        let path = path.to_str().ok_or_else(|| invalid("is not valid UTF-8"))?;
+        if !path.starts_with('/') {
+            return Err(invalid("must start with /"));
+        }
+        let trimmed = match path.trim_end_matches('/') {
+            "" => "/",
+            trimmed => trimmed,
+        };
+        if trimmed.len() > MAX_LENGTH {
+            return Err(invalid("is longer than 1011 characters"));
+        }
+        if let Some(c) = trimmed
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !"_.-/".contains(*c))
+        {
+            return Err(invalid(&format!("contains the invalid character '{}'", c)));
+        }
+        let segments = trimmed[1..].split('/').collect::<Vec<_>>();
+        if trimmed != "/" {
+            if segments.iter().any(|segment| segment.is_empty()) {
+                return Err(invalid("contains an empty segment"));
+            }
+            if segments.len() > MAX_DEPTH {
+                return Err(invalid("is nested more than 15 levels deep"));
+            }
+        }
+        Ok(ParameterPath(trimmed.to_string()))
+    }
+
+    /// Returns the path as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `name` relative to this path when it is nested below it
+    pub fn relative<'a>(
+        &self,
+        name: &'a str,
+    ) -> Option<&'a str> {
+        relative(&self.0, name)
+    }
+}
+
+impl FromStr for ParameterPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParameterPath::new(s)
+    }
+}
+
+impl AsRef<Path> for ParameterPath {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl From<ParameterPath> for String {
+    fn from(path: ParameterPath) -> Self {
+        path.0
+    }
+}
+
+impl fmt::Display for ParameterPath {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(fmt, "{}", self.0)
+    }
+}
+
+/// Fails when `name` begins with a root reserved by AWS, which may be read but not written
+pub(crate) fn writable(name: &str) -> Result<(), Error> {
+    let root = name
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if RESERVED.iter().any(|reserved| root.starts_with(reserved)) {
+        Err(Error::InvalidPath(
+            name.into(),
+            "may not begin with aws or ssm".into(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns `name` relative to `prefix` when it is nested below it
+pub(crate) fn relative<'a>(
+    prefix: &str,
+    name: &'a str,
+) -> Option<&'a str> {
+    let prefix = prefix.trim_end_matches('/');
+    if !name.starts_with(prefix) {
+        return None;
+    }
+    let rest = &name[prefix.len()..];
+    let relative = rest.trim_start_matches('/');
+    if relative.len() < rest.len() && !relative.is_empty() {
+        Some(relative)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{relative, writable, ParameterPath};
+    use crate::Error;
+
+    #[test]
+    fn validates_and_normalizes_paths() {
+        assert_eq!(
+            Ok("/app/prod"),
+            ParameterPath::new("/app/prod/")
+                .as_ref()
+                .map(ParameterPath::as_str)
+        );
+        assert_eq!(
+            Ok("/"),
+            ParameterPath::new("/").as_ref().map(ParameterPath::as_str)
+        );
+        for (path, reason) in &[
+            ("app/prod", "must start with /"),
+            ("/app//prod", "contains an empty segment"),
+            ("/app/pr$d", "contains the invalid character '$'"),
+        ] {
+            assert_eq!(
+                Err(Error::InvalidPath(path.to_string(), reason.to_string())),
+                ParameterPath::new(path)
+            );
+        }
+        assert!(ParameterPath::new(format!("/{}", vec!["a"; 16].join("/"))).is_err());
+        assert!(ParameterPath::new(format!("/{}", "a".repeat(1011))).is_err());
+        assert!(ParameterPath::new("/aws/service/global-infrastructure").is_ok());
+    }
+
+    #[test]
+    fn rejects_writes_below_reserved_roots() {
+        for path in &["/AWS/service", "/ssm", "ssm-app/db"] {
+            assert_eq!(
+                Err(Error::InvalidPath(
+                    path.to_string(),
+                    "may not begin with aws or ssm".into()
+                )),
+                writable(path)
+            );
+        }
+        assert_eq!(Ok(()), writable("/app/aws"));
+    }
+
+    #[test]
+    fn resolves_relative_names() {
+        assert_eq!(Some("db/host"), relative("/app", "/app/db/host"));
+        assert_eq!(Some("db/host"), relative("/app/", "/app/db/host"));
+        assert_eq!(Some("app/db"), relative("/", "/app/db"));
+        assert_eq!(None, relative("/app", "/apple/db"));
+        assert_eq!(None, relative("/app", "/app"));
+        assert_eq!(None, relative("/é", "/éé"));
+    }
+}
@@ -0,0 +1,249 @@
+// Std lib
+use std::{collections::BTreeMap, fmt};
+
+// Ours
+use crate::{Parameter, ParameterType};
+
+/// Replaces values of `SecureString` parameters in a [Plan](struct.Plan.html)
+const REDACTED: &str = "[REDACTED]";
+
+/// The difference between a desired and a stored parameter
+///
+/// Values of `SecureString` parameters are redacted
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    name: String,
+    type_: Option<ParameterType>,
+    current: Option<String>,
+    desired: Option<String>,
+}
+
+impl Difference {
+    fn new(
+        name: String,
+        type_: Option<ParameterType>,
+        current: Option<&Parameter>,
+        desired: Option<&Parameter>,
+    ) -> Self {
+        let secure = current
+            .iter()
+            .chain(desired.iter())
+            .any(|param| param.type_ == Some(ParameterType::SecureString));
+        let value = |param: &Parameter| {
+            if secure {
+                REDACTED.to_string()
+            } else {
+                param.value.clone()
+            }
+        };
+        Difference {
+            name,
+            type_,
+            current: current.map(value),
+            desired: desired.map(value),
+        }
+    }
+
+    /// Returns the fully qualified name of the parameter
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type of the desired parameter, or of the stored parameter when
+    /// it is to be deleted
+    pub fn parameter_type(&self) -> Option<ParameterType> {
+        self.type_
+    }
+
+    /// Returns the stored value, if any
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Returns the desired value, if any
+    pub fn desired(&self) -> Option<&str> {
+        self.desired.as_deref()
+    }
+}
+
+/// The parameters which would be added, changed and deleted to make the parameters
+/// stored under a prefix match a desired value
+///
+/// Created with [EnvyStore::plan](struct.EnvyStore.html#method.plan). Differences are
+/// ordered by name and display as one line per parameter, prefixed with `+`, `~` or `-`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Plan {
+    additions: Vec<Difference>,
+    changes: Vec<Difference>,
+    deletions: Vec<Difference>,
+}
+
+impl Plan {
+    /// Compares `current` parameters with `desired` parameters
+    ///
+    /// Parameters of the same name differ when their values differ or when both types
+    /// are known and differ
+    pub(crate) fn new(
+        current: Vec<Parameter>,
+        desired: Vec<Parameter>,
+    ) -> Self {
+        let mut current = current
+            .into_iter()
+            .map(|param| (param.name.clone(), param))
+            .collect::<BTreeMap<_, _>>();
+        let desired = desired
+            .into_iter()
+            .map(|param| (param.name.clone(), param))
+            .collect::<BTreeMap<_, _>>();
+        let mut plan = Plan::default();
+        for (name, desired) in desired {
+            match current.remove(&name) {
+                None => {
+                    plan.additions
+                        .push(Difference::new(name, desired.type_, None, Some(&desired)))
+                }
+                Some(stored) => {
+                    let retyped = match (stored.type_, desired.type_) {
+                        (Some(stored), Some(desired)) => stored != desired,
+                        _ => false,
+                    };
+                    if retyped || stored.value != desired.value {
+                        plan.changes.push(Difference::new(
+                            name,
+                            desired.type_,
+                            Some(&stored),
+                            Some(&desired),
+                        ))
+                    }
+                }
+            }
+        }
+        plan.deletions = current
+            .into_iter()
+            .map(|(name, stored)| Difference::new(name, stored.type_, Some(&stored), None))
+            .collect();
+        plan
+    }
+
+    /// Returns parameters which are desired but not stored
+    pub fn additions(&self) -> &[Difference] {
+        &self.additions
+    }
+
+    /// Returns parameters which are stored with a different value or type than desired
+    pub fn changes(&self) -> &[Difference] {
+        &self.changes
+    }
+
+    /// Returns parameters which are stored but not desired
+    pub fn deletions(&self) -> &[Difference] {
+        &self.deletions
+    }
+
+    /// Returns true when stored parameters already match those desired
+    pub fn is_empty(&self) -> bool {
+        self.additions.is_empty() && self.changes.is_empty() && self.deletions.is_empty()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        let lines = self
+            .additions
+            .iter()
+            .map(|diff| ('+', diff))
+            .chain(self.changes.iter().map(|diff| ('~', diff)))
+            .chain(self.deletions.iter().map(|diff| ('-', diff)));
+        for (i, (sign, diff)) in lines.enumerate() {
+            if i > 0 {
+                writeln!(fmt)?;
+            }
+            write!(fmt, "{} {}", sign, diff.name)?;
+            if let Some(type_) = diff.type_ {
+                write!(fmt, " ({})", type_)?;
+            }
+            match (&diff.current, &diff.desired) {
+                (Some(current), Some(desired)) => write!(fmt, " = {} -> {}", current, desired)?,
+                (None, Some(value)) | (Some(value), None) => write!(fmt, " = {}", value)?,
+                (None, None) => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Plan;
+    use crate::{Parameter, ParameterType};
+
+    #[test]
+    fn plans_additions_changes_and_deletions() {
+        let plan = Plan::new(
+            vec![
+                Parameter::typed("/app/host", "localhost", ParameterType::String),
+                Parameter::typed("/app/hosts", "a,b", ParameterType::String),
+                Parameter::typed("/app/port", "5432", ParameterType::String),
+                Parameter::typed("/app/stale", "old", ParameterType::String),
+            ],
+            vec![
+                Parameter::typed("/app/host", "example.com", ParameterType::String),
+                Parameter::typed("/app/hosts", "a,b", ParameterType::StringList),
+                Parameter::typed("/app/port", "5432", ParameterType::String),
+                Parameter::typed("/app/region", "us-east-1", ParameterType::String),
+            ],
+        );
+        assert_eq!(
+            vec!["/app/region"],
+            plan.additions()
+                .iter()
+                .map(|diff| diff.name())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["/app/host", "/app/hosts"],
+            plan.changes()
+                .iter()
+                .map(|diff| diff.name())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["/app/stale"],
+            plan.deletions()
+                .iter()
+                .map(|diff| diff.name())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "+ /app/region (String) = us-east-1\n\
+             ~ /app/host (String) = localhost -> example.com\n\
+             ~ /app/hosts (StringList) = a,b -> a,b\n\
+             - /app/stale (String) = old",
+            plan.to_string()
+        );
+        let unchanged = vec![Parameter::new("/app/host", "localhost")];
+        assert!(Plan::new(unchanged.clone(), unchanged).is_empty());
+    }
+
+    #[test]
+    fn redacts_secure_values() {
+        let plan = Plan::new(
+            vec![Parameter::typed(
+                "/app/password",
+                "hunter2",
+                ParameterType::String,
+            )],
+            vec![Parameter::typed(
+                "/app/password",
+                "hunter3",
+                ParameterType::SecureString,
+            )],
+        );
+        assert_eq!(Some("[REDACTED]"), plan.changes()[0].current());
+        assert_eq!(Some("[REDACTED]"), plan.changes()[0].desired());
+        assert!(!plan.to_string().contains("hunter"));
+    }
+}
@@ -0,0 +1,136 @@
+// Std lib
+use std::fmt;
+
+// Third party
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+/// A value which is redacted when formatted and zeroed when dropped
+///
+/// `Secret` deserializes from the same values as the type it wraps, making it suitable
+/// for fields resolved from `SecureString` parameters. The wrapped value is only
+/// available through [expose](#method.expose)
+///
+/// When written with [to_path](fn.to_path.html), secrets are stored as `SecureString`
+/// parameters. Note that other serializers see the wrapped value
+///
+/// # Example
+///
+/// ```
+/// use envy_store::Secret;
+///
+/// let password = Secret::new(String::from("hunter2"));
+/// assert_eq!("[REDACTED]", format!("{}", password));
+/// assert_eq!("hunter2", password.expose());
+/// ```
+#[derive(Clone, Default)]
+pub struct Secret<T>(T)
+where
+    T: Zeroize;
+
+impl<T> Secret<T>
+where
+    T: Zeroize,
+{
+    /// Wraps a secret value
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// Returns the secret value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Secret<T>
+where
+    T: Zeroize,
+{
+    fn from(value: T) -> Self {
+        Secret::new(value)
+    }
+}
+
+impl<T> Drop for Secret<T>
+where
+    T: Zeroize,
+{
+    fn drop(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+impl<T> fmt::Debug for Secret<T>
+where
+    T: Zeroize,
+{
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        fmt.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T> fmt::Display for Secret<T>
+where
+    T: Zeroize,
+{
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        fmt.write_str("[REDACTED]")
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Secret<T>
+where
+    T: Deserialize<'de> + Zeroize,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Secret)
+    }
+}
+
+impl<T> Serialize for Secret<T>
+where
+    T: Serialize + Zeroize,
+{
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        crate::secure(&self.0, serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Secret;
+    use serde::{
+        de::{value::Error, IntoDeserializer},
+        Deserialize,
+    };
+
+    #[test]
+    fn redacts_when_formatted() {
+        let secret = Secret::new(String::from("hunter2"));
+        assert_eq!("Secret([REDACTED])", format!("{:?}", secret));
+        assert_eq!("[REDACTED]", secret.to_string());
+    }
+
+    #[test]
+    fn deserializes_transparently() {
+        let secret =
+            Secret::<String>::deserialize(IntoDeserializer::<Error>::into_deserializer("hunter2"));
+        assert_eq!(Ok("hunter2"), secret.as_ref().map(|s| s.expose().as_str()));
+    }
+}
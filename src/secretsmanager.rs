@@ -0,0 +1,372 @@
+// Std lib
+use std::{error::Error as StdError, fmt, path::Path, sync::Arc};
+
+// Third party
+use futures::{
+    future::{self, Loop},
+    stream, Future, Stream,
+};
+use rusoto_secretsmanager::{
+    GetSecretValueError, GetSecretValueRequest, ListSecretsError, ListSecretsRequest,
+    SecretsManager, SecretsManagerClient,
+};
+use serde_json::Value;
+
+// Ours
+use crate::{Error, Options, Parameter, ParameterSource, ParameterType, Parameters};
+
+/// The most characters Secrets Manager accepts in a secret name
+const MAX_LENGTH: usize = 512;
+
+/// The most secret values requested at a time
+const MAX_CONCURRENT_REQUESTS: usize = 10;
+
+/// Represents possible Secrets Manager request failures
+#[derive(Debug, PartialEq)]
+pub enum SecretsManagerError {
+    /// Returned when listing secrets fails
+    List(ListSecretsError),
+    /// Returned when getting a secret's value fails
+    Get(GetSecretValueError),
+}
+
+impl StdError for SecretsManagerError {
+    fn description(&self) -> &str {
+        match self {
+            SecretsManagerError::List(e) => e.description(),
+            SecretsManagerError::Get(e) => e.description(),
+        }
+    }
+}
+
+impl fmt::Display for SecretsManagerError {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            SecretsManagerError::List(e) => write!(fmt, "{}", e),
+            SecretsManagerError::Get(e) => write!(fmt, "{}", e),
+        }
+    }
+}
+
+/// Resolves parameters from AWS Secrets Manager
+///
+/// Secrets whose names start with the path prefix followed by a `/` are resolved. A leading `/`
+/// is optional for secret names, so the secret `app/prod/db` is resolved under the prefix `/app/prod`.
+/// Prefixes may contain letters, numbers and the characters `/`, `_`, `+`, `=`, `.`, `@` and `-`.
+/// Secrets holding a JSON object are expanded so that each key is resolved as if it were
+/// nested below the secret's name.
+///
+/// Secrets Manager can not list secrets by prefix, so every secret in the account and region
+/// is listed, a page at a time, on each load before the values of those under the prefix are
+/// requested, at most 10 at a time. For accounts with many secrets, consider wrapping this
+/// source in a [CachedSource](struct.CachedSource.html)
+///
+/// Requires the `secretsmanager` feature along with the `secretsmanager:ListSecrets` and
+/// `secretsmanager:GetSecretValue` IAM permissions
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{EnvyStore, SecretsManagerSource};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Db {
+///   username: String,
+///   password: String,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///   // resolved from a secret named app/prod/db holding
+///   // {"username":"...","password":"..."}
+///   db: Db,
+/// }
+///
+/// let config = EnvyStore::builder()
+///   .source(SecretsManagerSource::default())
+///   .load::<Config, _>("/app/prod");
+/// ```
+#[derive(Clone)]
+pub struct SecretsManagerSource<C = SecretsManagerClient> {
+    client: Arc<C>,
+}
+
+impl Default for SecretsManagerSource<SecretsManagerClient> {
+    fn default() -> Self {
+        SecretsManagerSource::new(SecretsManagerClient::new(Default::default()))
+    }
+}
+
+impl<C> SecretsManagerSource<C>
+where
+    C: SecretsManager,
+{
+    /// Creates a new source from a `rusoto_secretsmanager::SecretsManager` implementation
+    pub fn new(client: C) -> Self {
+        SecretsManagerSource {
+            client: Arc::new(client),
+        }
+    }
+}
+
+impl<C> ParameterSource for SecretsManagerSource<C>
+where
+    C: SecretsManager + Send + Sync + 'static,
+{
+    fn validate_prefix(
+        &self,
+        path_prefix: &Path,
+    ) -> Result<String, Error> {
+        prefix(path_prefix)
+    }
+
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters {
+        let lister = self.client.clone();
+        let getter = self.client.clone();
+        let page_size = options.page_size();
+        let recursive = options.recursive();
+        let prefix = path_prefix.trim_end_matches('/').to_string();
+        let names = future::loop_fn(
+            (None, Vec::new()),
+            move |(next_token, mut names): (Option<String>, Vec<String>)| {
+                lister
+                    .list_secrets(ListSecretsRequest {
+                        max_results: page_size,
+                        next_token,
+                    })
+                    .map_err(|e| Error::SecretsManager(SecretsManagerError::List(e)))
+                    .map(move |resp| {
+                        names.extend(
+                            resp.secret_list
+                                .unwrap_or_default()
+                                .into_iter()
+                                .filter_map(|secret| secret.name),
+                        );
+                        match resp.next_token {
+                            Some(next) if !next.is_empty() => Loop::Continue((Some(next), names)),
+                            _ => Loop::Break(names),
+                        }
+                    })
+            },
+        );
+        Box::new(names.and_then(move |names| {
+            let secrets = names.into_iter().filter_map(move |secret_id| {
+                match relative(&secret_id, &prefix) {
+                    Some(rel) if recursive || !rel.contains('/') => {
+                        let name = format!("{}/{}", prefix, rel);
+                        Some((secret_id, name))
+                    }
+                    _ => None,
+                }
+            });
+            stream::iter_ok(secrets)
+                .map(move |(secret_id, name)| {
+                    getter
+                        .get_secret_value(GetSecretValueRequest {
+                            secret_id,
+                            ..GetSecretValueRequest::default()
+                        })
+                        .map_err(|e| Error::SecretsManager(SecretsManagerError::Get(e)))
+                        .map(move |resp| match resp.secret_string {
+                            Some(value) => expand(name, value),
+                            None => Vec::new(),
+                        })
+                })
+                .buffered(MAX_CONCURRENT_REQUESTS)
+                .concat2()
+        }))
+    }
+}
+
+/// Validates a secret name prefix, removing any trailing `/`
+fn prefix(path_prefix: &Path) -> Result<String, Error> {
+    let invalid =
+        |reason: &str| Error::InvalidPath(path_prefix.display().to_string(), reason.into());
+    let prefix = path_prefix
+        .to_str()
+        .ok_or_else(|| invalid("is not valid UTF-8"))?
+        .trim_end_matches('/');
+    if prefix.trim_start_matches('/').is_empty() {
+        return Err(invalid("must name a secret path"));
+    }
+    if prefix.len() > MAX_LENGTH {
+        return Err(invalid("is longer than 512 characters"));
+    }
+    if let Some(c) = prefix
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !"/_+=.@-".contains(*c))
+    {
+        return Err(invalid(&format!("contains the invalid character '{}'", c)));
+    }
+    Ok(prefix.to_string())
+}
+
+/// Returns `name` relative to `prefix`, ignoring leading `/`s, when `name` is below `prefix`
+fn relative<'a>(
+    name: &'a str,
+    prefix: &str,
+) -> Option<&'a str> {
+    let name = name.trim_start_matches('/');
+    let prefix = prefix.trim_start_matches('/');
+    if name.len() > prefix.len() + 1
+        && name.starts_with(prefix)
+        && name[prefix.len()..].starts_with('/')
+    {
+        Some(&name[prefix.len() + 1..])
+    } else {
+        None
+    }
+}
+
+/// Expands a secret holding a JSON object into one parameter per key.
+/// Any other value is resolved as is
+fn expand(
+    name: String,
+    value: String,
+) -> Vec<Parameter> {
+    fn flatten(
+        name: String,
+        value: Value,
+        parameters: &mut Vec<Parameter>,
+    ) {
+        let value = match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    flatten(format!("{}/{}", name, key), value, parameters);
+                }
+                return;
+            }
+            Value::String(value) => value,
+            other => other.to_string(),
+        };
+        parameters.push(Parameter::typed(name, value, ParameterType::SecureString));
+    }
+    let mut parameters = Vec::new();
+    match serde_json::from_str(&value) {
+        Ok(object @ Value::Object(_)) => flatten(name, object, &mut parameters),
+        _ => flatten(name, Value::String(value), &mut parameters),
+    }
+    parameters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, prefix, relative, SecretsManagerSource};
+    use crate::{EnvyStore, Error, Parameter, ParameterType};
+    use futures::Future;
+    use rusoto_mock::{
+        MockCredentialsProvider, MockRequestDispatcher, MultipleMockRequestDispatcher,
+    };
+    use rusoto_secretsmanager::SecretsManagerClient;
+    use serde::Deserialize;
+    use std::path::Path;
+
+    fn secret(
+        name: &str,
+        value: &str,
+    ) -> Parameter {
+        Parameter::typed(name, value, ParameterType::SecureString)
+    }
+
+    #[test]
+    fn resolves_names_relative_to_prefix() {
+        assert_eq!(Some("db"), relative("app/prod/db", "/app/prod"));
+        assert_eq!(
+            Some("db/password"),
+            relative("/app/prod/db/password", "app/prod")
+        );
+        assert_eq!(None, relative("app/production/db", "/app/prod"));
+        assert_eq!(None, relative("app/prod", "/app/prod"));
+    }
+
+    #[test]
+    fn validates_prefixes() {
+        assert_eq!(Ok("app/prod".to_string()), prefix(Path::new("app/prod/")));
+        assert_eq!(
+            Ok("/team+ops/db=main@us".to_string()),
+            prefix(Path::new("/team+ops/db=main@us"))
+        );
+        assert_eq!(
+            Err(Error::InvalidPath(
+                "/app/pr$d".into(),
+                "contains the invalid character '$'".into()
+            )),
+            prefix(Path::new("/app/pr$d"))
+        );
+        assert!(prefix(Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn expands_json_objects() {
+        assert_eq!(
+            vec![
+                secret("/app/db/password", "hunter2"),
+                secret("/app/db/port", "5432"),
+            ],
+            expand(
+                "/app/db".into(),
+                r#"{"password":"hunter2","port":5432}"#.into()
+            )
+        );
+        assert_eq!(
+            vec![secret("/app/token", "abc")],
+            expand("/app/token".into(), "abc".into())
+        );
+    }
+
+    #[test]
+    fn resolves_secrets_from_client() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Db {
+            password: String,
+        }
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            db: Db,
+            region: String,
+        }
+        let response = |body| MockRequestDispatcher::with_status(200).with_json_body(body);
+        let mock = MultipleMockRequestDispatcher::new(vec![
+            response(serde_json::json!({
+                "SecretList": [{ "Name": "app/prod/db" }, { "Name": "app/production/db" }],
+                "NextToken": "next"
+            })),
+            response(serde_json::json!({
+                "SecretList": [{ "Name": "/app/prod/nested/token" }, { "Name": "app/prod/region" }]
+            })),
+            response(serde_json::json!({
+                "Name": "app/prod/db",
+                "SecretString": r#"{"password":"hunter2"}"#
+            })),
+            response(serde_json::json!({
+                "Name": "app/prod/region",
+                "SecretString": "us-east-1"
+            })),
+        ]);
+        assert_eq!(
+            Ok(Config {
+                db: Db {
+                    password: "hunter2".into()
+                },
+                region: "us-east-1".into(),
+            }),
+            EnvyStore::builder()
+                .source(SecretsManagerSource::new(SecretsManagerClient::new_with(
+                    mock,
+                    MockCredentialsProvider,
+                    Default::default()
+                )))
+                .recursive(false)
+                .load::<Config, _>("/app/prod")
+                .wait()
+        );
+    }
+}
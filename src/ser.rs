@@ -0,0 +1,610 @@
+// Std lib
+use std::{error::Error as StdError, fmt};
+
+// Third party
+use serde::ser::{self, Impossible, Serialize};
+
+// Ours
+use crate::{Parameter, ParameterType};
+
+/// The name of the newtype struct marking values stored as `SecureString`s
+pub(crate) const SECURE: &str = "$envy_store::secure";
+
+/// Serializes a field as a `SecureString` parameter
+///
+/// Intended for use with serde's `serialize_with` field attribute when writing
+/// values with [to_path](fn.to_path.html). Other serializers see the value itself.
+/// [Secret](struct.Secret.html) values are always serialized this way
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///   #[serde(serialize_with = "envy_store::secure")]
+///   api_key: String,
+/// }
+/// ```
+pub fn secure<T, S>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: ser::Serializer,
+{
+    serializer.serialize_newtype_struct(SECURE, value)
+}
+
+/// Flattens `value` into parameters named below `path_prefix`
+///
+/// Fields of structs and entries of maps become path segments, sequences become
+/// `StringList`s, values marked [secure](fn.secure.html) become `SecureString`s and all
+/// other values become `String`s. `None` values are skipped, while empty values and
+/// sequences, which parameter store can not hold, are rejected
+pub(crate) fn parameters<T>(
+    path_prefix: &str,
+    value: &T,
+) -> Result<Vec<Parameter>, String>
+where
+    T: Serialize + ?Sized,
+{
+    let mut parameters = Vec::new();
+    value
+        .serialize(Flattener {
+            name: path_prefix.trim_end_matches('/').to_string(),
+            secure: false,
+            parameters: &mut parameters,
+        })
+        .map_err(|err| err.0)?;
+    Ok(parameters)
+}
+
+#[derive(Debug)]
+pub(crate) struct SerializeError(String);
+
+impl ser::Error for SerializeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        SerializeError(msg.to_string())
+    }
+}
+
+impl StdError for SerializeError {
+    fn description(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(
+        &self,
+        fmt: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(fmt, "{}", self.0)
+    }
+}
+
+fn unsupported(
+    kind: &str,
+    name: &str,
+) -> SerializeError {
+    SerializeError(format!("can not store {} as parameter {}", kind, name))
+}
+
+/// Serializes a value as one or more parameters named below `name`
+struct Flattener<'a> {
+    name: String,
+    secure: bool,
+    parameters: &'a mut Vec<Parameter>,
+}
+
+impl<'a> Flattener<'a> {
+    fn push(
+        self,
+        value: String,
+    ) -> Result<(), SerializeError> {
+        // parameter store rejects empty values
+        if value.is_empty() {
+            return Err(unsupported("an empty value", &self.name));
+        }
+        let type_ = if self.secure {
+            ParameterType::SecureString
+        } else {
+            ParameterType::String
+        };
+        self.parameters
+            .push(Parameter::typed(self.name, value, type_));
+        Ok(())
+    }
+
+    /// Returns a flattener for a nested value, which is secure when this value is
+    fn nested(
+        &mut self,
+        segment: &str,
+    ) -> Flattener<'_> {
+        Flattener {
+            name: format!("{}/{}", self.name, segment),
+            secure: self.secure,
+            parameters: self.parameters,
+        }
+    }
+}
+
+macro_rules! serialize_displayed {
+    ($($method:ident: $ty:ty,)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                self.push(value.to_string())
+            }
+        )*
+    }
+}
+
+impl<'a> ser::Serializer for Flattener<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = List<'a>;
+    type SerializeTuple = List<'a>;
+    type SerializeTupleStruct = List<'a>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = Branch<'a>;
+    type SerializeStruct = Branch<'a>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    serialize_displayed! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    }
+
+    fn serialize_bytes(
+        self,
+        _: &[u8],
+    ) -> Result<(), SerializeError> {
+        Err(unsupported("bytes", &self.name))
+    }
+
+    fn serialize_none(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(
+        self,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _: &'static str,
+    ) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), SerializeError> {
+        self.push(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(
+        mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        if name == SECURE {
+            self.secure = true;
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(unsupported("enum variants with data", &self.name))
+    }
+
+    fn serialize_seq(
+        self,
+        _: Option<usize>,
+    ) -> Result<List<'a>, SerializeError> {
+        if self.secure {
+            return Err(unsupported("a sequence of secure values", &self.name));
+        }
+        Ok(List {
+            name: self.name,
+            values: Vec::new(),
+            parameters: self.parameters,
+        })
+    }
+
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<List<'a>, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<List<'a>, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(unsupported("enum variants with data", &self.name))
+    }
+
+    fn serialize_map(
+        self,
+        _: Option<usize>,
+    ) -> Result<Branch<'a>, SerializeError> {
+        Ok(Branch {
+            flattener: self,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Branch<'a>, SerializeError> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(unsupported("enum variants with data", &self.name))
+    }
+}
+
+/// Collects the elements of a sequence into a `StringList` parameter
+pub(crate) struct List<'a> {
+    name: String,
+    values: Vec<String>,
+    parameters: &'a mut Vec<Parameter>,
+}
+
+impl<'a> List<'a> {
+    fn push<T>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut element = Vec::new();
+        value.serialize(Flattener {
+            name: self.name.clone(),
+            secure: false,
+            parameters: &mut element,
+        })?;
+        match element.as_slice() {
+            [Parameter {
+                type_: Some(ParameterType::SecureString),
+                ..
+            }] => Err(unsupported("a sequence of secure values", &self.name)),
+            [Parameter { name, value, .. }] if name == &self.name && !value.contains(',') => {
+                self.values.push(value.clone());
+                Ok(())
+            }
+            _ => Err(unsupported(
+                "sequences of values which are not text or contain commas",
+                &self.name,
+            )),
+        }
+    }
+
+    fn finish(self) -> Result<(), SerializeError> {
+        if self.values.is_empty() {
+            return Err(unsupported("an empty sequence", &self.name));
+        }
+        self.parameters.push(Parameter::typed(
+            self.name,
+            self.values.join(","),
+            ParameterType::StringList,
+        ));
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeSeq for List<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for List<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for List<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.finish()
+    }
+}
+
+/// Serializes the entries of a map or the fields of a struct as nested parameters
+pub(crate) struct Branch<'a> {
+    flattener: Flattener<'a>,
+    key: Option<String>,
+}
+
+impl<'a> ser::SerializeMap for Branch<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T>(
+        &mut self,
+        key: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut segment = Vec::new();
+        key.serialize(Flattener {
+            name: String::new(),
+            secure: false,
+            parameters: &mut segment,
+        })?;
+        match segment.pop() {
+            Some(Parameter { value, .. }) if segment.is_empty() => {
+                self.key = Some(value);
+                Ok(())
+            }
+            _ => Err(unsupported(
+                "maps whose keys are not text",
+                &self.flattener.name,
+            )),
+        }
+    }
+
+    fn serialize_value<T>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self.key.take().unwrap_or_default();
+        value.serialize(self.flattener.nested(&key))
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for Branch<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self.flattener.nested(key))
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parameters;
+    use crate::{Parameter, ParameterType, Secret};
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn flattens_values_into_typed_parameters() {
+        #[derive(Serialize)]
+        struct Db {
+            host: String,
+            port: u16,
+            password: Secret<String>,
+        }
+        #[derive(Serialize)]
+        struct Config {
+            db: Db,
+            hosts: Vec<String>,
+            tags: BTreeMap<String, String>,
+            #[serde(serialize_with = "crate::secure")]
+            token: String,
+            region: Option<String>,
+        }
+        let config = Config {
+            db: Db {
+                host: "localhost".into(),
+                port: 5432,
+                password: Secret::new("hunter2".into()),
+            },
+            hosts: vec!["a".into(), "b".into()],
+            tags: vec![("team".to_string(), "platform".to_string())]
+                .into_iter()
+                .collect(),
+            token: "abc".into(),
+            region: None,
+        };
+        assert_eq!(
+            Ok(vec![
+                Parameter::typed("/app/db/host", "localhost", ParameterType::String),
+                Parameter::typed("/app/db/port", "5432", ParameterType::String),
+                Parameter::typed("/app/db/password", "hunter2", ParameterType::SecureString),
+                Parameter::typed("/app/hosts", "a,b", ParameterType::StringList),
+                Parameter::typed("/app/tags/team", "platform", ParameterType::String),
+                Parameter::typed("/app/token", "abc", ParameterType::SecureString),
+            ]),
+            parameters("/app/", &config)
+        );
+    }
+
+    #[test]
+    fn marks_values_nested_below_secure_values() {
+        #[derive(Serialize)]
+        struct Db {
+            user: String,
+            password: String,
+        }
+        #[derive(Serialize)]
+        struct Config {
+            #[serde(serialize_with = "crate::secure")]
+            db: Db,
+            #[serde(serialize_with = "crate::secure")]
+            keys: BTreeMap<String, String>,
+        }
+        let config = Config {
+            db: Db {
+                user: "admin".into(),
+                password: "hunter2".into(),
+            },
+            keys: vec![("stripe".to_string(), "sk_live".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        assert_eq!(
+            Ok(vec![
+                Parameter::typed("/app/db/user", "admin", ParameterType::SecureString),
+                Parameter::typed("/app/db/password", "hunter2", ParameterType::SecureString),
+                Parameter::typed("/app/keys/stripe", "sk_live", ParameterType::SecureString),
+            ]),
+            parameters("/app", &config)
+        );
+    }
+
+    #[test]
+    fn rejects_list_elements_containing_commas() {
+        assert!(parameters("/app", &vec!["a,b"]).is_err());
+    }
+
+    #[test]
+    fn rejects_sequences_of_secure_values() {
+        #[derive(Serialize)]
+        struct Config {
+            keys: Vec<Secret<String>>,
+        }
+        let config = Config {
+            keys: vec![Secret::new("hunter2".into())],
+        };
+        assert_eq!(
+            Err("can not store a sequence of secure values as parameter /app/keys".to_string()),
+            parameters("/app", &config)
+        );
+    }
+
+    #[test]
+    fn rejects_empty_values() {
+        let mut config = BTreeMap::new();
+        config.insert("host", vec!["localhost"]);
+        config.insert("replicas", Vec::new());
+        assert_eq!(
+            Err("can not store an empty sequence as parameter /app/replicas".to_string()),
+            parameters("/app", &config)
+        );
+        assert_eq!(
+            Err("can not store an empty value as parameter /app/host".to_string()),
+            parameters(
+                "/app",
+                &vec![("host", "")].into_iter().collect::<BTreeMap<_, _>>()
+            )
+        );
+        assert!(parameters("/app", &vec!["a", ""]).is_err());
+    }
+}
@@ -0,0 +1,341 @@
+// Std lib
+use std::path::Path;
+
+// Third party
+use futures::{future, Future};
+use rusoto_ssm::ParameterStringFilter;
+
+// Ours
+use crate::{Error, ParameterPath, ParameterType, Selector};
+
+/// A named value resolved from a [ParameterSource](trait.ParameterSource.html)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// The fully qualified name, e.g. `/sweet-app/prod/db-pass`
+    pub name: String,
+    /// The value, decrypted when requested and supported by the source
+    pub value: String,
+    /// The type of value, when known
+    pub type_: Option<ParameterType>,
+    /// The version of the value, when known
+    pub version: Option<i64>,
+}
+
+impl Parameter {
+    /// Creates a new parameter of unknown type and version
+    pub fn new<N, V>(
+        name: N,
+        value: V,
+    ) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        Parameter {
+            name: name.into(),
+            value: value.into(),
+            type_: None,
+            version: None,
+        }
+    }
+
+    /// Creates a new parameter of a known type and unknown version
+    pub fn typed<N, V>(
+        name: N,
+        value: V,
+        type_: ParameterType,
+    ) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        Parameter {
+            type_: Some(type_),
+            ..Parameter::new(name, value)
+        }
+    }
+}
+
+/// Settings that sources may use to narrow or otherwise
+/// affect the parameters they resolve
+///
+/// Sources are free to ignore settings which do not apply to them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub(crate) recursive: bool,
+    pub(crate) decrypt: bool,
+    pub(crate) page_size: Option<i64>,
+    pub(crate) parameter_filters: Vec<ParameterStringFilter>,
+    pub(crate) selector: Option<Selector>,
+    pub(crate) key_selectors: Vec<(String, Selector)>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            recursive: true,
+            decrypt: true,
+            page_size: None,
+            parameter_filters: Vec::new(),
+            selector: None,
+            key_selectors: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// Whether parameters nested more than one level below the path prefix
+    /// should be resolved
+    pub fn recursive(&self) -> bool {
+        self.recursive
+    }
+
+    /// Whether encrypted values should be decrypted
+    pub fn decrypt(&self) -> bool {
+        self.decrypt
+    }
+
+    /// The maximum number of parameters to request at a time
+    pub fn page_size(&self) -> Option<i64> {
+        self.page_size
+    }
+
+    /// Filters a parameter must match to be resolved
+    pub fn parameter_filters(&self) -> &[ParameterStringFilter] {
+        &self.parameter_filters
+    }
+
+    /// The selector pinning the parameter `name`, relative to the path prefix,
+    /// to a version or label, if any
+    pub fn selector(
+        &self,
+        name: &str,
+    ) -> Option<&Selector> {
+        let name = key(name);
+        self.key_selectors
+            .iter()
+            .rev()
+            .find(|(key, _)| key == &name)
+            .map(|(_, selector)| selector)
+            .or(self.selector.as_ref())
+    }
+
+    /// Returns options whose key selectors name parameters in full, as they would be
+    /// below `path_prefix`, for resolving parameters by name
+    pub(crate) fn below(
+        &self,
+        path_prefix: &str,
+    ) -> Options {
+        let prefix = path_prefix.trim_end_matches('/');
+        Options {
+            key_selectors: self
+                .key_selectors
+                .iter()
+                .map(|(name, selector)| (key(&format!("{}/{}", prefix, name)), selector.clone()))
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Normalizes a parameter name relative to a path prefix for comparison.
+/// Parameter names are case sensitive, so only surrounding slashes are trimmed
+pub(crate) fn key(name: &str) -> String {
+    name.trim_matches('/').into()
+}
+
+/// The future returned by a [ParameterSource](trait.ParameterSource.html)
+pub type Parameters = Box<dyn Future<Item = Vec<Parameter>, Error = Error> + Send>;
+
+/// A backend from which parameters may be resolved by path prefix
+///
+/// Parameter store is supported with [SsmSource](struct.SsmSource.html). A `Vec<Parameter>`
+/// is also a source, which is useful as an in-memory fake in tests
+///
+/// # Example
+///
+/// ```
+/// use envy_store::{EnvyStore, Parameter};
+/// use futures::Future;
+/// use std::collections::HashMap;
+///
+/// let config = EnvyStore::builder()
+///   .source(vec![Parameter::new("/demo/foo", "bar")])
+///   .load::<HashMap<String, String>, _>("/demo")
+///   .wait();
+/// assert_eq!(Some(&"bar".to_string()), config.unwrap().get("foo"));
+/// ```
+pub trait ParameterSource {
+    /// Resolves all parameters whose names start with `path_prefix` followed by a `/`
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters;
+
+    /// Validates and normalizes a path prefix before parameters are resolved under it,
+    /// failing with `Error::InvalidPath`
+    ///
+    /// By default prefixes must be valid parameter store paths, see
+    /// [ParameterPath](struct.ParameterPath.html)
+    fn validate_prefix(
+        &self,
+        path_prefix: &Path,
+    ) -> Result<String, Error> {
+        ParameterPath::new(path_prefix).map(String::from)
+    }
+
+    /// Resolves parameters by their full names, failing with `Error::MissingParameters`
+    /// when any do not exist
+    ///
+    /// By default, parameters directly below each name's parent path are resolved with
+    /// [parameters](#tymethod.parameters) and those named are kept
+    fn parameters_named(
+        &self,
+        names: &[String],
+        options: &Options,
+    ) -> Parameters {
+        let mut parents: Vec<&str> = Vec::new();
+        for name in names {
+            let parent = name.rfind('/').map(|end| &name[..end]).unwrap_or_default();
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+        let options = Options {
+            recursive: false,
+            ..options.clone()
+        };
+        let requests = parents
+            .into_iter()
+            .map(|parent| self.parameters(parent, &options))
+            .collect::<Vec<_>>();
+        let names = names.to_vec();
+        Box::new(future::join_all(requests).and_then(move |resolved| {
+            let resolved = resolved.into_iter().flatten().collect::<Vec<_>>();
+            let (found, missing): (Vec<_>, Vec<_>) = names
+                .into_iter()
+                .partition(|name| resolved.iter().any(|param| &param.name == name));
+            if !missing.is_empty() {
+                return Err(Error::MissingParameters(missing));
+            }
+            Ok(resolved
+                .into_iter()
+                .filter(|param| found.contains(&param.name))
+                .collect())
+        }))
+    }
+}
+
+impl ParameterSource for Vec<Parameter> {
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters {
+        let prefix = format!("{}/", path_prefix.trim_end_matches('/'));
+        let recursive = options.recursive();
+        Box::new(future::ok(
+            self.iter()
+                .filter(|param| {
+                    param.name.starts_with(&prefix)
+                        && (recursive || !param.name[prefix.len()..].contains('/'))
+                })
+                .cloned()
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Options, Parameter, ParameterSource};
+    use crate::{Error, Selector};
+    use futures::Future;
+
+    #[test]
+    fn key_selectors_override_global_selector() {
+        let options = Options {
+            selector: Some(Selector::label("release")),
+            key_selectors: vec![("db/password".into(), Selector::Version(3))],
+            ..Options::default()
+        };
+        assert_eq!(
+            Some(&Selector::Version(3)),
+            options.selector("/db/password/")
+        );
+        assert_eq!(
+            Some(&Selector::label("release")),
+            options.selector("/DB/Password")
+        );
+        assert_eq!(
+            Some(&Selector::label("release")),
+            options.selector("db/host")
+        );
+        assert_eq!(None, Options::default().selector("db/host"));
+        let named = options.below("/app/prod/");
+        assert_eq!(
+            Some(&Selector::Version(3)),
+            named.selector("/app/prod/db/password")
+        );
+        assert_eq!(
+            Some(&Selector::label("release")),
+            named.selector("db/password")
+        );
+    }
+
+    #[test]
+    fn resolves_parameters_by_name() {
+        let source = vec![
+            Parameter::new("/shared/region", "us-east-1"),
+            Parameter::new("/app/prod/db/host", "localhost"),
+            Parameter::new("/app/prod/name", "app"),
+        ];
+        assert_eq!(
+            Ok(vec![
+                Parameter::new("/shared/region", "us-east-1"),
+                Parameter::new("/app/prod/name", "app"),
+            ]),
+            source
+                .parameters_named(
+                    &["/shared/region".into(), "/app/prod/name".into()],
+                    &Options::default()
+                )
+                .wait()
+        );
+        assert_eq!(
+            Err(Error::MissingParameters(vec!["/app/prod/db".into()])),
+            source
+                .parameters_named(&["/app/prod/db".into()], &Options::default())
+                .wait()
+        );
+    }
+
+    #[test]
+    fn vec_resolves_parameters_under_prefix() {
+        let source = vec![
+            Parameter::new("/app/foo", "bar"),
+            Parameter::new("/app/db/host", "localhost"),
+            Parameter::new("/application/foo", "baz"),
+        ];
+        assert_eq!(
+            Ok(vec![
+                Parameter::new("/app/foo", "bar"),
+                Parameter::new("/app/db/host", "localhost"),
+            ]),
+            source.parameters("/app", &Options::default()).wait()
+        );
+        assert_eq!(
+            Ok(vec![Parameter::new("/app/foo", "bar")]),
+            source
+                .parameters(
+                    "/app",
+                    &Options {
+                        recursive: false,
+                        ..Options::default()
+                    }
+                )
+                .wait()
+        );
+    }
+}
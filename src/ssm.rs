@@ -0,0 +1,311 @@
+// Std lib
+use std::{collections::HashMap, sync::Arc};
+
+// Third party
+use futures::{
+    future::{self, Loop},
+    stream, Future, Stream,
+};
+use rusoto_ssm::{
+    GetParametersByPathRequest, GetParametersRequest, Parameter as SsmParameter,
+    PutParameterRequest, Ssm, SsmClient,
+};
+
+// Ours
+use crate::{
+    path, Error, Options, Parameter, ParameterPath, ParameterSource, ParameterType, Parameters,
+};
+
+/// The most names parameter store accepts in a single `GetParameters` request
+const MAX_NAMES: usize = 10;
+
+/// Resolves parameters from AWS Parameter Store using `GetParametersByPath`
+///
+/// Every page of results is requested before the returned future resolves. Parameters
+/// pinned by a [Selector](enum.Selector.html) are then requested by name with
+/// `GetParameters`, which is also used to resolve parameters by name
+#[derive(Clone)]
+pub struct SsmSource<C = SsmClient> {
+    client: Arc<C>,
+}
+
+impl Default for SsmSource<SsmClient> {
+    fn default() -> Self {
+        SsmSource::new(SsmClient::new(Default::default()))
+    }
+}
+
+impl<C> SsmSource<C>
+where
+    C: Ssm,
+{
+    /// Creates a new source from a `rusoto_ssm::Ssm` implementation
+    pub fn new(client: C) -> Self {
+        SsmSource {
+            client: Arc::new(client),
+        }
+    }
+}
+
+impl<C> ParameterSource for SsmSource<C>
+where
+    C: Ssm + Send + Sync + 'static,
+{
+    fn parameters(
+        &self,
+        path_prefix: &str,
+        options: &Options,
+    ) -> Parameters {
+        let client = self.client.clone();
+        let pinner = self.client.clone();
+        let options = options.clone();
+        let pin_options = options.clone();
+        let path = path_prefix.to_string();
+        let prefix = path.clone();
+        let latest = future::loop_fn((None, Vec::new()), move |(next_token, mut parameters)| {
+            client
+                .get_parameters_by_path(request(&options, path.clone(), next_token))
+                .map_err(Error::from)
+                .map(move |resp| {
+                    parameters.extend(
+                        resp.parameters
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter_map(parameter),
+                    );
+                    match resp.next_token {
+                        Some(next) if !next.is_empty() => Loop::Continue((Some(next), parameters)),
+                        _ => Loop::Break(parameters),
+                    }
+                })
+        });
+        Box::new(latest.and_then(move |parameters| pin(pinner, &pin_options, &prefix, parameters)))
+    }
+
+    fn parameters_named(
+        &self,
+        names: &[String],
+        options: &Options,
+    ) -> Parameters {
+        let names = names
+            .iter()
+            .map(|name| match options.selector(name) {
+                Some(selector) => format!("{}:{}", name, selector),
+                None => name.clone(),
+            })
+            .collect::<Vec<_>>();
+        Box::new(get(&*self.client, &names, options.decrypt()))
+    }
+}
+
+/// Replaces parameters pinned by a selector with their selected versions
+fn pin<C>(
+    client: Arc<C>,
+    options: &Options,
+    path_prefix: &str,
+    parameters: Vec<Parameter>,
+) -> Parameters
+where
+    C: Ssm + Send + Sync + 'static,
+{
+    let names = selected(options, path_prefix, &parameters);
+    if names.is_empty() {
+        return Box::new(future::ok(parameters));
+    }
+    Box::new(get(&*client, &names, options.decrypt()).map(move |pinned| {
+        let mut pinned = pinned
+            .into_iter()
+            .map(|param| (param.name.clone(), param))
+            .collect::<HashMap<_, _>>();
+        parameters
+            .into_iter()
+            .map(|param| pinned.remove(&param.name).unwrap_or(param))
+            .collect()
+    }))
+}
+
+/// Requests parameters by name in batches, failing with the names of any which do not exist
+fn get<C>(
+    client: &C,
+    names: &[String],
+    decrypt: bool,
+) -> impl Future<Item = Vec<Parameter>, Error = Error> + Send
+where
+    C: Ssm,
+{
+    let requests = names
+        .chunks(MAX_NAMES)
+        .map(|names| {
+            client
+                .get_parameters(GetParametersRequest {
+                    names: names.to_vec(),
+                    with_decryption: Some(decrypt),
+                })
+                .map_err(Error::from)
+        })
+        .collect::<Vec<_>>();
+    future::join_all(requests).and_then(|results| {
+        let mut missing = Vec::new();
+        let mut parameters = Vec::new();
+        for result in results {
+            missing.extend(result.invalid_parameters.unwrap_or_default());
+            parameters.extend(
+                result
+                    .parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(parameter),
+            );
+        }
+        if missing.is_empty() {
+            Ok(parameters)
+        } else {
+            Err(Error::MissingParameters(missing))
+        }
+    })
+}
+
+/// Writes parameters one at a time, overwriting any existing values
+///
+/// Parameters without a type are written as `String`s
+pub(crate) fn put<C>(
+    client: C,
+    parameters: Vec<Parameter>,
+) -> impl Future<Item = (), Error = Error> + Send
+where
+    C: Ssm + Send + Sync + 'static,
+{
+    future::result(writable(&parameters)).and_then(move |_| {
+        stream::iter_ok(parameters).for_each(move |param| {
+            client
+                .put_parameter(PutParameterRequest {
+                    name: param.name,
+                    value: param.value,
+                    type_: param.type_.unwrap_or(ParameterType::String).to_string(),
+                    overwrite: Some(true),
+                    ..PutParameterRequest::default()
+                })
+                .map(|_| ())
+                .map_err(Error::from)
+        })
+    })
+}
+
+/// Fails with the first parameter whose name parameter store would not accept, so that
+/// nothing is written when any name is invalid
+fn writable(parameters: &[Parameter]) -> Result<(), Error> {
+    parameters.iter().try_for_each(|param| {
+        ParameterPath::new(&param.name).and_then(|_| path::writable(&param.name))
+    })
+}
+
+/// Returns `name:selector` for each parameter pinned by a selector
+fn selected(
+    options: &Options,
+    path_prefix: &str,
+    parameters: &[Parameter],
+) -> Vec<String> {
+    parameters
+        .iter()
+        .filter_map(|param| {
+            options
+                .selector(path::relative(path_prefix, &param.name).unwrap_or_default())
+                .map(|selector| format!("{}:{}", param.name, selector))
+        })
+        .collect()
+}
+
+fn request(
+    options: &Options,
+    path: String,
+    next_token: Option<String>,
+) -> GetParametersByPathRequest {
+    GetParametersByPathRequest {
+        next_token,
+        path,
+        with_decryption: Some(options.decrypt()),
+        recursive: Some(options.recursive()),
+        max_results: options.page_size(),
+        parameter_filters: if options.parameter_filters().is_empty() {
+            None
+        } else {
+            Some(options.parameter_filters().to_vec())
+        },
+    }
+}
+
+/// Converts a parameter store parameter, skipping those without a name or value
+fn parameter(param: SsmParameter) -> Option<Parameter> {
+    match (param.name, param.value) {
+        (Some(name), Some(value)) => Some(Parameter {
+            // names requested with a selector may be returned with it
+            name: match name.find(':') {
+                Some(selector) => name[..selector].to_string(),
+                None => name,
+            },
+            value,
+            type_: param.type_.and_then(|type_| type_.parse().ok()),
+            version: param.version,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{selected, writable};
+    use crate::{Error, Options, Parameter, Selector};
+
+    #[test]
+    fn rejects_invalid_names_before_writing() {
+        let deep = format!("/{}", vec!["a"; 16].join("/"));
+        for (name, reason) in &[
+            ("/app/db host", "contains the invalid character ' '"),
+            ("/app/pa$$word", "contains the invalid character '$'"),
+            (deep.as_str(), "is nested more than 15 levels deep"),
+            ("/aws", "may not begin with aws or ssm"),
+        ] {
+            assert_eq!(
+                Err(Error::InvalidPath(name.to_string(), reason.to_string())),
+                writable(&[
+                    Parameter::new("/app/db/host", "localhost"),
+                    Parameter::new(*name, "value"),
+                ])
+            );
+        }
+        assert_eq!(
+            Ok(()),
+            writable(&[Parameter::new("/app/db/host", "localhost")])
+        );
+    }
+
+    #[test]
+    fn selects_pinned_parameters() {
+        let options = Options {
+            key_selectors: vec![("db/password".into(), Selector::Version(3))],
+            ..Options::default()
+        };
+        let parameters = vec![
+            Parameter::new("/app/db/host", "localhost"),
+            Parameter::new("/app/db/password", "hunter2"),
+        ];
+        assert_eq!(
+            vec!["/app/db/password:3".to_string()],
+            selected(&options, "/app", &parameters)
+        );
+        assert_eq!(
+            vec![
+                "/app/db/host:release".to_string(),
+                "/app/db/password:release".to_string()
+            ],
+            selected(
+                &Options {
+                    selector: Some(Selector::label("release")),
+                    ..Options::default()
+                },
+                "/app/",
+                &parameters
+            )
+        );
+    }
+}
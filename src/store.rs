@@ -0,0 +1,1175 @@
+// Std lib
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// Third party
+use futures::{future, stream, Future, Stream};
+use rusoto_ssm::{ParameterStringFilter, Ssm};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{
+    runtime::current_thread::Runtime,
+    timer::{Interval, Timeout},
+};
+
+// Ours
+use crate::{
+    de::{self, Entry, Settings},
+    deserialize, entries, path, ser, source, CachedSource, EnvOverlay, Error, Filter, Normalize,
+    Options, Parameter, ParameterSource, Plan, Selector, SsmSource, Watched,
+};
+
+/// Configures an [EnvyStore](struct.EnvyStore.html)
+///
+/// Created with [EnvyStore::builder](struct.EnvyStore.html#method.builder)
+pub struct Builder<S = SsmSource> {
+    source: S,
+    options: Options,
+    env: Option<EnvOverlay>,
+    settings: Settings,
+}
+
+impl Default for Builder<SsmSource> {
+    fn default() -> Self {
+        Builder {
+            source: SsmSource::default(),
+            options: Options::default(),
+            env: None,
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl<S> Builder<S> {
+    /// Sets the `rusoto_ssm::Ssm` implementation used to make requests.
+    /// Defaults to an `SsmClient` for the default region
+    pub fn client<C>(
+        self,
+        client: C,
+    ) -> Builder<SsmSource<C>>
+    where
+        C: Ssm + Send + Sync + 'static,
+    {
+        self.source(SsmSource::new(client))
+    }
+
+    /// Sets the source parameters are resolved from.
+    /// Defaults to parameter store
+    pub fn source<N>(
+        self,
+        source: N,
+    ) -> Builder<N>
+    where
+        N: ParameterSource,
+    {
+        Builder {
+            source,
+            options: self.options,
+            env: self.env,
+            settings: self.settings,
+        }
+    }
+
+    /// Caches parameters resolved by the current source for `ttl`.
+    /// Clones of the built `EnvyStore` share the same cache, see
+    /// [CachedSource](struct.CachedSource.html)
+    pub fn cache(
+        self,
+        ttl: Duration,
+    ) -> Builder<CachedSource<S>>
+    where
+        S: ParameterSource,
+    {
+        let source = CachedSource::new(self.source, ttl);
+        Builder {
+            source,
+            options: self.options,
+            env: self.env,
+            settings: self.settings,
+        }
+    }
+
+    /// Sets whether parameters nested more than one level below the path prefix
+    /// are resolved. Defaults to `true`
+    pub fn recursive(
+        mut self,
+        recursive: bool,
+    ) -> Self {
+        self.options.recursive = recursive;
+        self
+    }
+
+    /// Sets whether `SecureString` values are decrypted. Defaults to `true`
+    pub fn decrypt(
+        mut self,
+        decrypt: bool,
+    ) -> Self {
+        self.options.decrypt = decrypt;
+        self
+    }
+
+    /// Sets the maximum number of parameters requested per page.
+    /// Defaults to the service default
+    pub fn page_size(
+        mut self,
+        page_size: i64,
+    ) -> Self {
+        self.options.page_size = Some(page_size);
+        self
+    }
+
+    /// Restricts resolved parameters to those matching `filter`.
+    /// May be called more than once, in which case parameters must match every filter
+    pub fn filter(
+        mut self,
+        filter: Filter,
+    ) -> Self {
+        self.options.parameter_filters.push(filter.into());
+        self
+    }
+
+    /// Appends a raw filter to the list sent with each request
+    pub fn parameter_filter(
+        mut self,
+        filter: ParameterStringFilter,
+    ) -> Self {
+        self.options.parameter_filters.push(filter);
+        self
+    }
+
+    /// Pins every parameter to a version or label. Defaults to the latest version
+    ///
+    /// Pinned parameters are resolved by name after listing those under the
+    /// path prefix. When a parameter has no such version, `Error::MissingParameters`
+    /// is returned. Only parameter store sources support selectors
+    pub fn selector(
+        mut self,
+        selector: Selector,
+    ) -> Self {
+        self.options.selector = Some(selector);
+        self
+    }
+
+    /// Pins the parameter `key`, relative to the path prefix, to a version or label,
+    /// taking precedence over any selector set with [selector](#method.selector)
+    ///
+    /// Parameters resolved with [load_names](struct.EnvyStore.html#method.load_names) have no
+    /// path prefix, so there `key` is a parameter's full name
+    pub fn key_selector<K>(
+        mut self,
+        key: K,
+        selector: Selector,
+    ) -> Self
+    where
+        K: AsRef<str>,
+    {
+        self.options
+            .key_selectors
+            .push((source::key(key.as_ref()), selector));
+        self
+    }
+
+    /// Sets whether sequence fields must be resolved from `StringList` parameters.
+    /// Defaults to `false`
+    ///
+    /// `StringList` values are always split on commas while values of other
+    /// parameter types are resolved as a single element. In strict mode, resolving
+    /// a sequence from a `String` or `SecureString` parameter fails instead.
+    /// Values whose parameter type is unknown, such as those overlaid from
+    /// environment variables, are split on commas in either mode
+    pub fn strict(
+        mut self,
+        strict: bool,
+    ) -> Self {
+        self.settings.strict = strict;
+        self
+    }
+
+    /// Decodes the value resolved for `key` as a JSON document, allowing
+    /// it to be deserialized into structured types such as maps, sequences or
+    /// nested structs. May be called more than once
+    ///
+    /// `key` is the name of a parameter relative to the path prefix, for example
+    /// `routes` or `db/replicas`.
+    ///
+    /// Requires the `json` feature
+    #[cfg(feature = "json")]
+    pub fn json_key<K>(
+        mut self,
+        key: K,
+    ) -> Self
+    where
+        K: AsRef<str>,
+    {
+        self.settings.json_keys.push(
+            key.as_ref()
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_string)
+                .collect(),
+        );
+        self
+    }
+
+    /// Sets how parameter names, relative to the path prefix, are converted into
+    /// the keys matched with fields. Defaults to lowercasing names
+    ///
+    /// When two parameters resolved under the same prefix convert to the same key,
+    /// `Error::Collision` is returned
+    pub fn normalize(
+        mut self,
+        normalize: Normalize,
+    ) -> Self {
+        self.settings.normalize = normalize;
+        self
+    }
+
+    /// Overlays process environment variables on resolved values
+    pub fn env_overlay(
+        mut self,
+        overlay: EnvOverlay,
+    ) -> Self {
+        self.env = Some(overlay);
+        self
+    }
+
+    /// Produces a configured `EnvyStore`
+    pub fn build(self) -> EnvyStore<S> {
+        EnvyStore {
+            source: self.source,
+            options: self.options,
+            env: self.env,
+            settings: self.settings,
+        }
+    }
+}
+
+impl<S> Builder<S>
+where
+    S: ParameterSource + Send,
+{
+    /// Shortcut for `build().load(path_prefix)`
+    pub fn load<T, P>(
+        self,
+        path_prefix: P,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        P: AsRef<Path>,
+    {
+        self.build().load(path_prefix)
+    }
+
+    /// Shortcut for `build().load_layered(path_prefixes)`
+    pub fn load_layered<T, I>(
+        self,
+        path_prefixes: I,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        self.build().load_layered(path_prefixes)
+    }
+
+    /// Shortcut for `build().load_names(names)`
+    pub fn load_names<T, I>(
+        self,
+        names: I,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.build().load_names(names)
+    }
+
+    /// Shortcut for `build().load_fields(path_prefix)`
+    pub fn load_fields<T, P>(
+        self,
+        path_prefix: P,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        P: AsRef<Path>,
+    {
+        self.build().load_fields(path_prefix)
+    }
+
+    /// Shortcut for `build().plan(path_prefix, desired)`
+    pub fn plan<T, P>(
+        self,
+        path_prefix: P,
+        desired: &T,
+    ) -> impl Future<Item = Plan, Error = Error> + Send
+    where
+        T: Serialize + ?Sized,
+        P: AsRef<Path>,
+    {
+        self.build().plan(path_prefix, desired)
+    }
+
+    /// Shortcut for `build().load_blocking(path_prefix, timeout)`
+    pub fn load_blocking<T, P>(
+        self,
+        path_prefix: P,
+        timeout: Option<Duration>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Send,
+        P: AsRef<Path>,
+    {
+        self.build().load_blocking(path_prefix, timeout)
+    }
+}
+
+impl<S> Builder<S>
+where
+    S: ParameterSource + Send + Sync + 'static,
+{
+    /// Shortcut for `build().watch(path_prefix, interval)`
+    pub fn watch<T, P>(
+        self,
+        path_prefix: P,
+        interval: Duration,
+    ) -> impl Future<Item = Watched<T>, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+        P: AsRef<Path>,
+    {
+        self.build().watch(path_prefix, interval)
+    }
+
+    /// Shortcut for `build().watch_layered(path_prefixes, interval)`
+    pub fn watch_layered<T, I>(
+        self,
+        path_prefixes: I,
+        interval: Duration,
+    ) -> impl Future<Item = Watched<T>, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        self.build().watch_layered(path_prefixes, interval)
+    }
+
+    /// Shortcut for `build().load_async(path_prefix)`
+    #[cfg(feature = "async")]
+    pub fn load_async<T, P>(
+        self,
+        path_prefix: P,
+    ) -> impl std::future::Future<Output = Result<T, Error>> + Send
+    where
+        T: DeserializeOwned + Send + 'static,
+        P: AsRef<Path>,
+    {
+        self.build().load_async(path_prefix)
+    }
+
+    /// Shortcut for `build().load_layered_async(path_prefixes)`
+    #[cfg(feature = "async")]
+    pub fn load_layered_async<T, I>(
+        self,
+        path_prefixes: I,
+    ) -> impl std::future::Future<Output = Result<T, Error>> + Send
+    where
+        T: DeserializeOwned + Send + 'static,
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        self.build().load_layered_async(path_prefixes)
+    }
+}
+
+/// Resolves values from a [ParameterSource](trait.ParameterSource.html) using configurable settings
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::EnvyStore;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///   foo: String,
+/// }
+///
+/// let config = EnvyStore::builder()
+///   .recursive(false)
+///   .page_size(10)
+///   .load::<Config, _>("/demo");
+/// ```
+#[derive(Clone)]
+pub struct EnvyStore<S = SsmSource> {
+    source: S,
+    options: Options,
+    env: Option<EnvOverlay>,
+    settings: Settings,
+}
+
+impl EnvyStore<SsmSource> {
+    /// Returns a new `Builder` using an `SsmClient` for the default region
+    pub fn builder() -> Builder<SsmSource> {
+        Builder::default()
+    }
+}
+
+impl<S> EnvyStore<S>
+where
+    S: ParameterSource + Send,
+{
+    /// Resolves values under `path_prefix` and deserializes them into
+    /// a typesafe struct
+    pub fn load<T, P>(
+        self,
+        path_prefix: P,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        P: AsRef<Path>,
+    {
+        self.load_layered(Some(path_prefix))
+    }
+
+    /// Resolves values under each of `path_prefixes` and deserializes them into
+    /// a typesafe struct
+    ///
+    /// Prefixes are resolved in order. When the same name, relative to its prefix, is found
+    /// under more than one prefix, the value from the later prefix is used
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use envy_store::EnvyStore;
+    /// use std::collections::HashMap;
+    ///
+    /// // `/app/prod/db-host` overrides `/app/default/db-host`
+    /// let config = EnvyStore::builder()
+    ///   .load_layered::<HashMap<String, String>, _>(vec!["/shared", "/app/default", "/app/prod"]);
+    /// ```
+    pub fn load_layered<T, I>(
+        self,
+        path_prefixes: I,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let EnvyStore {
+            source,
+            options,
+            env,
+            settings,
+        } = self;
+        future::result(prefixes(&source, path_prefixes))
+            .and_then(move |prefixes| {
+                stream::iter_ok(prefixes)
+                    .and_then(move |prefix| {
+                        source
+                            .parameters(&prefix, &options)
+                            .map(move |parameters| (prefix, parameters))
+                    })
+                    .collect()
+            })
+            .and_then(move |layers| resolve(env.as_ref(), &settings, layers))
+    }
+
+    /// Resolves parameters by their full names and deserializes them into
+    /// a typesafe struct
+    ///
+    /// Values are keyed by the last segment of their names, so `/shared/region` provides
+    /// the field `region`. When any named parameter does not exist,
+    /// `Error::MissingParameters` is returned
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use envy_store::EnvyStore;
+    /// use std::collections::HashMap;
+    ///
+    /// let config = EnvyStore::builder()
+    ///   .load_names::<HashMap<String, String>, _>(vec!["/shared/region", "/app/prod/db-host"]);
+    /// ```
+    pub fn load_names<T, I>(
+        self,
+        names: I,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into).collect();
+        self.named(names, false)
+    }
+
+    /// Resolves a parameter below `path_prefix` for each field of the struct `T`
+    /// by name and deserializes them
+    ///
+    /// Only the fields of `T` itself are resolved, so nested structs are not supported.
+    /// See [load_names](#method.load_names) for how parameters are resolved. Parameters
+    /// that do not exist are left out rather than failing with `Error::MissingParameters`,
+    /// so `Option` and `#[serde(default)]` fields may be absent while absent required
+    /// fields fail to deserialize
+    pub fn load_fields<T, P>(
+        self,
+        path_prefix: P,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+        P: AsRef<Path>,
+    {
+        future::result(self.source.validate_prefix(path_prefix.as_ref())).and_then(move |prefix| {
+            let names = de::fields::<T>()
+                .iter()
+                .map(|field| format!("{}/{}", prefix.trim_end_matches('/'), field))
+                .collect::<Vec<_>>();
+            // key selectors are relative to the prefix while names are resolved in full
+            let mut store = self;
+            store.options = store.options.below(&prefix);
+            store.named(names, true)
+        })
+    }
+
+    /// Resolves parameters by their full names, optionally retrying without those
+    /// reported missing
+    fn named<T>(
+        self,
+        names: Vec<String>,
+        allow_missing: bool,
+    ) -> impl Future<Item = T, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send,
+    {
+        let EnvyStore {
+            source,
+            options,
+            env,
+            settings,
+        } = self;
+        // missing fields can only be attributed to a parameter when all share a parent
+        let mut parents = names.iter().map(|name| parent(name));
+        let expected_prefix = match parents.next() {
+            Some(first) if parents.all(|parent| parent == first) => Some(first.to_string()),
+            _ => None,
+        };
+        source
+            .parameters_named(&names, &options)
+            .or_else(move |err| match err {
+                Error::MissingParameters(ref missing) if allow_missing => {
+                    let present = names
+                        .into_iter()
+                        .filter(|name| !missing.iter().any(|missing| is_named(missing, name)))
+                        .collect::<Vec<_>>();
+                    future::Either::A(source.parameters_named(&present, &options))
+                }
+                err => future::Either::B(future::err(err)),
+            })
+            .and_then(move |parameters| {
+                let resolved = parameters
+                    .into_iter()
+                    .map(|param| Entry {
+                        path: vec![param.name[parent(&param.name).len()..]
+                            .trim_start_matches('/')
+                            .to_string()],
+                        name: param.name,
+                        value: param.value,
+                        type_: param.type_,
+                    })
+                    .collect::<Vec<_>>();
+                settings.normalize.distinct(&resolved)?;
+                resolve_entries(
+                    env.as_ref(),
+                    &settings,
+                    expected_prefix.as_ref().map(String::as_str),
+                    resolved,
+                )
+            })
+    }
+
+    /// Compares the values stored under `path_prefix` with those
+    /// [to_path](fn.to_path.html) would write for `desired`
+    ///
+    /// `desired` may be a typesafe struct or a map of names, relative to `path_prefix`, to
+    /// values. Stored values are resolved with this store's options, so they should be
+    /// decrypted for `SecureString` values to be compared. No parameters are written
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use envy_store::EnvyStore;
+    /// use futures::Future;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut desired = HashMap::new();
+    /// desired.insert("db-host", "localhost");
+    /// let plan = EnvyStore::builder()
+    ///   .plan("/app/prod", &desired)
+    ///   .map(|plan| println!("{}", plan));
+    /// ```
+    pub fn plan<T, P>(
+        self,
+        path_prefix: P,
+        desired: &T,
+    ) -> impl Future<Item = Plan, Error = Error> + Send
+    where
+        T: Serialize + ?Sized,
+        P: AsRef<Path>,
+    {
+        let EnvyStore {
+            source, options, ..
+        } = self;
+        let planned = source
+            .validate_prefix(path_prefix.as_ref())
+            .and_then(|prefix| path::writable(&prefix).map(|_| prefix))
+            .and_then(|prefix| {
+                ser::parameters(&prefix, desired)
+                    .map(|desired| (prefix, desired))
+                    .map_err(Error::Serialize)
+            });
+        future::result(planned).and_then(move |(prefix, desired)| {
+            source
+                .parameters(&prefix, &options)
+                .map(move |current| Plan::new(current, desired))
+        })
+    }
+
+    /// Resolves values under `path_prefix` and deserializes them into
+    /// a typesafe struct, blocking the current thread until done
+    ///
+    /// When a `timeout` is provided and loading takes longer, `Error::Timeout` is returned
+    ///
+    /// # Panics
+    ///
+    /// A runtime is started to drive loading and tokio runtimes can not be nested, so this
+    /// panics when called from within a tokio runtime. Use [load](#method.load) there instead
+    pub fn load_blocking<T, P>(
+        self,
+        path_prefix: P,
+        timeout: Option<Duration>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Send,
+        P: AsRef<Path>,
+    {
+        let mut runtime = Runtime::new().map_err(|e| Error::Runtime(e.to_string()))?;
+        let load = self.load(path_prefix);
+        match timeout {
+            Some(duration) => runtime
+                .block_on(Timeout::new(load, duration))
+                .map_err(|err| {
+                    if err.is_elapsed() {
+                        Error::Timeout(duration)
+                    } else if err.is_timer() {
+                        Error::Runtime(err.to_string())
+                    } else {
+                        err.into_inner().unwrap_or(Error::Timeout(duration))
+                    }
+                }),
+            None => runtime.block_on(load),
+        }
+    }
+}
+
+impl<S> EnvyStore<S>
+where
+    S: ParameterSource + Send + Sync + 'static,
+{
+    /// Resolves values under `path_prefix` into a [Watched](struct.Watched.html) handle which
+    /// reloads them every `interval`
+    ///
+    /// The returned future must be run on a tokio runtime, which reloading is spawned onto.
+    /// When a reload fails, or its values can not be deserialized, the previous value is kept
+    pub fn watch<T, P>(
+        self,
+        path_prefix: P,
+        interval: Duration,
+    ) -> impl Future<Item = Watched<T>, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+        P: AsRef<Path>,
+    {
+        self.watch_layered(Some(path_prefix), interval)
+    }
+
+    /// Resolves values under each of `path_prefixes` into a [Watched](struct.Watched.html)
+    /// handle which reloads them every `interval`
+    ///
+    /// See [load_layered](#method.load_layered) for how values from each prefix are combined
+    /// and [watch](#method.watch) for how they are reloaded
+    pub fn watch_layered<T, I>(
+        self,
+        path_prefixes: I,
+        interval: Duration,
+    ) -> impl Future<Item = Watched<T>, Error = Error> + Send
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let store = Arc::new(self);
+        let initial = {
+            let store = store.clone();
+            future::result(prefixes(&store.source, path_prefixes)).and_then(move |prefixes| {
+                layers(store, prefixes.clone()).map(move |initial| (prefixes, initial))
+            })
+        };
+        initial.and_then(move |(prefixes, initial)| {
+            let watched = Watched::new(resolve(
+                store.env.as_ref(),
+                &store.settings,
+                initial.clone(),
+            )?);
+            let updater = watched.updater();
+            let closed = updater.clone();
+            tokio::spawn(
+                Interval::new(Instant::now() + interval, interval)
+                    .map_err(|_| ())
+                    .take_while(move |_| Ok(!closed.is_closed()))
+                    .fold(initial, move |previous, _| {
+                        let store = store.clone();
+                        let updater = updater.clone();
+                        layers(store.clone(), prefixes.clone()).then(move |result| {
+                            Ok::<_, ()>(match result {
+                                Ok(current) if current != previous => {
+                                    match resolve(
+                                        store.env.as_ref(),
+                                        &store.settings,
+                                        current.clone(),
+                                    ) {
+                                        Ok(value) => {
+                                            updater.update(value);
+                                            current
+                                        }
+                                        Err(_) => previous,
+                                    }
+                                }
+                                _ => previous,
+                            })
+                        })
+                    })
+                    .map(|_| ()),
+            );
+            Ok(watched)
+        })
+    }
+
+    /// Resolves values under `path_prefix` and deserializes them into
+    /// a typesafe struct, returning a `std::future::Future`
+    ///
+    /// Sources return futures 0.1 futures and `SsmSource` requests need a tokio 0.1 reactor,
+    /// so loading is driven by a runtime of its own on a background thread. The returned
+    /// future only waits for the result, so it may be awaited from any executor
+    #[cfg(feature = "async")]
+    pub fn load_async<T, P>(
+        self,
+        path_prefix: P,
+    ) -> impl std::future::Future<Output = Result<T, Error>> + Send
+    where
+        T: DeserializeOwned + Send + 'static,
+        P: AsRef<Path>,
+    {
+        self.load_layered_async(Some(path_prefix))
+    }
+
+    /// Resolves values under each of `path_prefixes` and deserializes them into
+    /// a typesafe struct, returning a `std::future::Future`
+    ///
+    /// See [load_layered](#method.load_layered) for how values from each prefix are combined
+    /// and [load_async](#method.load_async) for how they are loaded
+    #[cfg(feature = "async")]
+    pub fn load_layered_async<T, I>(
+        self,
+        path_prefixes: I,
+    ) -> impl std::future::Future<Output = Result<T, Error>> + Send
+    where
+        T: DeserializeOwned + Send + 'static,
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let prefixes = prefixes(&self.source, path_prefixes);
+        async move {
+            let load = self.load_layered::<T, _>(prefixes?);
+            let (sender, receiver) = futures03::channel::oneshot::channel();
+            std::thread::Builder::new()
+                .name("envy-store".into())
+                .spawn(move || {
+                    let loaded = Runtime::new()
+                        .map_err(|err| Error::Runtime(err.to_string()))
+                        .and_then(|mut runtime| runtime.block_on(load));
+                    let _ = sender.send(loaded);
+                })
+                .map_err(|err| Error::Runtime(err.to_string()))?;
+            receiver.await.unwrap_or_else(|_| {
+                Err(Error::Runtime(
+                    "loading thread exited without a result".into(),
+                ))
+            })
+        }
+    }
+}
+
+/// Resolves each of `prefixes` from a shared store, paired with the prefix
+fn layers<S>(
+    store: Arc<EnvyStore<S>>,
+    prefixes: Vec<String>,
+) -> impl Future<Item = Vec<(String, Vec<Parameter>)>, Error = Error> + Send
+where
+    S: ParameterSource + Send + Sync + 'static,
+{
+    stream::iter_ok(prefixes)
+        .and_then(move |prefix| {
+            store
+                .source
+                .parameters(&prefix, &store.options)
+                .map(move |parameters| (prefix, parameters))
+        })
+        .collect()
+}
+
+/// Deserializes layers of parameters, each paired with the prefix they were
+/// resolved under, applying any environment overlay
+///
+/// Missing values are reported as expected under the last prefix
+fn resolve<T>(
+    env: Option<&EnvOverlay>,
+    settings: &Settings,
+    layers: Vec<(String, Vec<Parameter>)>,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned + Send,
+{
+    let expected_prefix = layers.last().map(|(prefix, _)| prefix.clone());
+    let mut resolved = Vec::new();
+    for (prefix, parameters) in layers {
+        let layer = entries(&prefix, parameters);
+        settings.normalize.distinct(&layer)?;
+        resolved.extend(layer);
+    }
+    resolve_entries(
+        env,
+        settings,
+        expected_prefix.as_ref().map(String::as_str),
+        resolved,
+    )
+}
+
+/// Deserializes entries, applying any environment overlay
+fn resolve_entries<T>(
+    env: Option<&EnvOverlay>,
+    settings: &Settings,
+    expected_prefix: Option<&str>,
+    resolved: Vec<Entry>,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned + Send,
+{
+    deserialize(
+        expected_prefix,
+        settings,
+        match env {
+            Some(overlay) => overlay.apply(resolved, &settings.normalize),
+            None => resolved,
+        },
+    )
+}
+
+/// Returns the path a parameter name is nested below
+fn parent(name: &str) -> &str {
+    name.rfind('/').map(|end| &name[..end]).unwrap_or_default()
+}
+
+/// Returns true when `reported`, which may carry a `:version` or `:label` selector,
+/// refers to the parameter `name`
+fn is_named(
+    reported: &str,
+    name: &str,
+) -> bool {
+    reported.starts_with(name)
+        && (reported.len() == name.len() || reported[name.len()..].starts_with(':'))
+}
+
+/// Validates and normalizes each of `path_prefixes` with `source`
+fn prefixes<S, I>(
+    source: &S,
+    path_prefixes: I,
+) -> Result<Vec<String>, Error>
+where
+    S: ParameterSource,
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
+{
+    path_prefixes
+        .into_iter()
+        .map(|prefix| source.validate_prefix(prefix.as_ref()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvyStore;
+    use crate::{Error, Options, Parameter, ParameterSource, Parameters};
+    use futures::{future, Future, Stream};
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+    use rusoto_ssm::SsmClient;
+    use serde::Deserialize;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+    use tokio::{runtime::current_thread::Runtime, timer::Delay};
+
+    #[derive(Clone)]
+    struct Rotating(Arc<Mutex<Vec<Parameter>>>);
+
+    impl ParameterSource for Rotating {
+        fn parameters(
+            &self,
+            path_prefix: &str,
+            options: &Options,
+        ) -> Parameters {
+            let parameters = self.0.lock().unwrap().clone();
+            parameters.parameters(path_prefix, options)
+        }
+    }
+
+    #[test]
+    fn loads_with_custom_options() {
+        let mock = MockRequestDispatcher::with_status(200).with_json_body(serde_json::json!({
+            "Parameters": [
+                {
+                    "Name": "/test/foo",
+                    "Value": "bar"
+                }
+            ]
+        }));
+        assert_eq!(
+            Ok(hashmap!("foo".to_string() => "bar".to_string())),
+            EnvyStore::builder()
+                .client(SsmClient::new_with(
+                    mock,
+                    MockCredentialsProvider,
+                    Default::default()
+                ))
+                .recursive(false)
+                .decrypt(false)
+                .page_size(10)
+                .load::<HashMap<String, String>, _>("/test")
+                .wait()
+        )
+    }
+
+    #[test]
+    fn loads_layers_from_source() {
+        assert_eq!(
+            Ok(hashmap!(
+                "region".to_string() => "us-east-1".to_string(),
+                "host".to_string() => "prod.example.com".to_string()
+            )),
+            EnvyStore::builder()
+                .source(vec![
+                    Parameter::new("/shared/region", "us-east-1"),
+                    Parameter::new("/shared/host", "localhost"),
+                    Parameter::new("/app/prod/host", "prod.example.com"),
+                ])
+                .load_layered::<HashMap<String, String>, _>(vec!["/shared", "/app/prod"])
+                .wait()
+        )
+    }
+
+    #[test]
+    fn loads_blocking() {
+        struct Pending;
+
+        impl ParameterSource for Pending {
+            fn parameters(
+                &self,
+                _: &str,
+                _: &Options,
+            ) -> Parameters {
+                Box::new(future::empty())
+            }
+        }
+
+        let timeout = Some(Duration::from_millis(10));
+        assert_eq!(
+            Ok(vec![("foo".to_string(), "bar".to_string())]
+                .into_iter()
+                .collect()),
+            EnvyStore::builder()
+                .source(vec![Parameter::new("/app/foo", "bar")])
+                .load_blocking::<HashMap<String, String>, _>("/app", timeout)
+        );
+        assert_eq!(
+            Err(Error::Timeout(Duration::from_millis(10))),
+            EnvyStore::builder()
+                .source(Pending)
+                .load_blocking::<HashMap<String, String>, _>("/app", timeout)
+        );
+    }
+
+    #[test]
+    fn loads_by_name() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            region: String,
+            host: String,
+        }
+        let source = vec![
+            Parameter::new("/shared/region", "us-east-1"),
+            Parameter::new("/app/prod/host", "prod.example.com"),
+            Parameter::new("/app/prod/region", "eu-west-1"),
+            Parameter::new("/app/prod/zone", "eu-west-1a"),
+        ];
+        let expected = Config {
+            region: "us-east-1".into(),
+            host: "prod.example.com".into(),
+        };
+        assert_eq!(
+            Ok(&expected),
+            EnvyStore::builder()
+                .source(source.clone())
+                .load_names::<Config, _>(vec!["/shared/region", "/app/prod/host"])
+                .wait()
+                .as_ref()
+        );
+        assert_eq!(
+            Ok(Config {
+                region: "eu-west-1".into(),
+                ..expected
+            }),
+            EnvyStore::builder()
+                .source(source.clone())
+                .load_fields::<Config, _>("/app/prod")
+                .wait()
+        );
+        // names with different parents leave nothing to attribute a missing value to
+        match EnvyStore::builder()
+            .source(source)
+            .load_names::<Config, _>(vec!["/shared/region", "/app/prod/zone"])
+            .wait()
+        {
+            Err(err) => assert_eq!("missing value for field `host`", err.to_string()),
+            Ok(config) => panic!("unexpected config {:?}", config),
+        }
+    }
+
+    #[test]
+    fn loads_fields_leaving_out_absent_parameters() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            host: String,
+            port: Option<u16>,
+            #[serde(default)]
+            debug: bool,
+        }
+        assert_eq!(
+            Ok(Config {
+                host: "localhost".into(),
+                port: None,
+                debug: false,
+            }),
+            EnvyStore::builder()
+                .source(vec![Parameter::new("/app/host", "localhost")])
+                .load_fields::<Config, _>("/app")
+                .wait()
+        );
+        match EnvyStore::builder()
+            .source(vec![Parameter::new("/app/port", "8080")])
+            .load_fields::<Config, _>("/app")
+            .wait()
+        {
+            Err(err) => assert_eq!(
+                "missing value for field `host`, expected parameter /app/host",
+                err.to_string()
+            ),
+            Ok(config) => panic!("unexpected config {:?}", config),
+        }
+    }
+
+    #[test]
+    fn watches_for_changes() {
+        let source = Rotating(Arc::new(Mutex::new(vec![Parameter::new(
+            "/app/password",
+            "hunter2",
+        )])));
+        let mut runtime = Runtime::new().unwrap();
+        let watched = runtime
+            .block_on(
+                EnvyStore::builder()
+                    .source(source.clone())
+                    .watch::<HashMap<String, String>, _>("/app", Duration::from_millis(10)),
+            )
+            .unwrap();
+        assert_eq!("hunter2", watched.get()["password"]);
+        *source.0.lock().unwrap() = vec![Parameter::new("/app/password", "hunter3")];
+        let (update, _) = runtime
+            .block_on(watched.subscribe().into_future())
+            .ok()
+            .unwrap();
+        assert_eq!("hunter3", update.unwrap()["password"]);
+        assert_eq!("hunter3", watched.get()["password"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn resolves_async_without_a_tokio_runtime() {
+        // timers, like rusoto's requests, need a tokio 0.1 runtime to be polled on
+        struct Delayed;
+
+        impl ParameterSource for Delayed {
+            fn parameters(
+                &self,
+                _: &str,
+                _: &Options,
+            ) -> Parameters {
+                Box::new(
+                    Delay::new(Instant::now() + Duration::from_millis(1))
+                        .map_err(|err| Error::Runtime(err.to_string()))
+                        .map(|_| vec![Parameter::new("/app/foo", "bar")]),
+                )
+            }
+        }
+
+        assert_eq!(
+            Ok(hashmap!("foo".to_string() => "bar".to_string())),
+            futures03::executor::block_on(
+                EnvyStore::builder()
+                    .source(Delayed)
+                    .load_async::<HashMap<String, String>, _>("/app")
+            )
+        )
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn loads_async() {
+        let mock = MockRequestDispatcher::with_status(200).with_json_body(serde_json::json!({
+            "Parameters": [
+                {
+                    "Name": "/test/foo",
+                    "Value": "bar"
+                }
+            ]
+        }));
+        assert_eq!(
+            Ok(hashmap!("foo".to_string() => "bar".to_string())),
+            futures03::executor::block_on(
+                EnvyStore::builder()
+                    .client(SsmClient::new_with(
+                        mock,
+                        MockCredentialsProvider,
+                        Default::default()
+                    ))
+                    .load_async::<HashMap<String, String>, _>("/test")
+            )
+        )
+    }
+}
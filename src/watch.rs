@@ -0,0 +1,180 @@
+// Std lib
+use std::sync::{Arc, Mutex, RwLock, Weak};
+
+// Third party
+use futures::{
+    sync::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    Poll, Stream,
+};
+
+struct Shared<T> {
+    value: RwLock<Arc<T>>,
+    subscribers: Mutex<Vec<UnboundedSender<Arc<T>>>>,
+}
+
+/// A handle to values which are periodically reloaded in the background
+///
+/// Created with [EnvyStore::watch](struct.EnvyStore.html#method.watch). Clones share the
+/// same value and reloading stops once every clone and every
+/// [Subscription](struct.Subscription.html) has been dropped
+///
+/// # Example
+///
+/// ```no_run
+/// use envy_store::{EnvyStore, Secret};
+/// use futures::{Future, Stream};
+/// use serde::Deserialize;
+/// use std::time::Duration;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///   password: Secret<String>,
+/// }
+///
+/// tokio::run(
+///   EnvyStore::builder()
+///     .build()
+///     .watch::<Config, _>("/demo", Duration::from_secs(30))
+///     .map_err(|err| eprintln!("{}", err))
+///     .and_then(|watched| {
+///       watched.subscribe().for_each(|_config| {
+///         println!("password rotated");
+///         Ok(())
+///       })
+///     }),
+/// );
+/// ```
+pub struct Watched<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for Watched<T> {
+    fn clone(&self) -> Self {
+        Watched {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Watched<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Watched {
+            shared: Arc::new(Shared {
+                value: RwLock::new(Arc::new(value)),
+                subscribers: Mutex::default(),
+            }),
+        }
+    }
+
+    pub(crate) fn updater(&self) -> Updater<T> {
+        Updater(Arc::downgrade(&self.shared))
+    }
+
+    /// Returns the most recently loaded value
+    pub fn get(&self) -> Arc<T> {
+        match self.shared.value.read() {
+            Ok(value) => value.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Returns a stream which yields each newly loaded value
+    ///
+    /// Values are only sent when reloaded parameters differ from those previously loaded.
+    /// The subscription keeps reloading going, even once this handle has been dropped
+    pub fn subscribe(&self) -> Subscription<T> {
+        let (sender, receiver) = unbounded();
+        if let Ok(mut subscribers) = self.shared.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        Subscription {
+            receiver,
+            _shared: self.shared.clone(),
+        }
+    }
+}
+
+/// A stream of the values loaded by a [Watched](struct.Watched.html) handle
+///
+/// Created with [Watched::subscribe](struct.Watched.html#method.subscribe)
+pub struct Subscription<T> {
+    receiver: UnboundedReceiver<Arc<T>>,
+    _shared: Arc<Shared<T>>,
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = Arc<T>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.receiver.poll()
+    }
+}
+
+/// Replaces the value of a `Watched` without keeping it alive
+pub(crate) struct Updater<T>(Weak<Shared<T>>);
+
+impl<T> Clone for Updater<T> {
+    fn clone(&self) -> Self {
+        Updater(self.0.clone())
+    }
+}
+
+impl<T> Updater<T> {
+    /// Returns true when every `Watched` handle has been dropped
+    pub(crate) fn is_closed(&self) -> bool {
+        self.0.upgrade().is_none()
+    }
+
+    /// Swaps in `value` and notifies subscribers
+    pub(crate) fn update(
+        &self,
+        value: T,
+    ) {
+        let shared = match self.0.upgrade() {
+            Some(shared) => shared,
+            None => return,
+        };
+        let value = Arc::new(value);
+        match shared.value.write() {
+            Ok(mut current) => *current = value.clone(),
+            Err(poisoned) => *poisoned.into_inner() = value.clone(),
+        }
+        if let Ok(mut subscribers) = shared.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.unbounded_send(value.clone()).is_ok());
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Watched;
+    use futures::{Future, Stream};
+
+    #[test]
+    fn updates_notify_subscribers() {
+        let watched = Watched::new(1);
+        let updates = watched.subscribe();
+        let updater = watched.updater();
+        updater.update(2);
+        assert_eq!(2, *watched.get());
+        let (update, _) = updates.into_future().wait().ok().unwrap();
+        assert_eq!(Some(2), update.map(|value| *value));
+        drop(watched);
+        assert!(updater.is_closed());
+    }
+
+    #[test]
+    fn subscriptions_outlive_handles() {
+        let watched = Watched::new(1);
+        let updates = watched.subscribe();
+        let updater = watched.updater();
+        drop(watched);
+        assert!(!updater.is_closed());
+        updater.update(2);
+        let (update, updates) = updates.into_future().wait().ok().unwrap();
+        assert_eq!(Some(2), update.map(|value| *value));
+        drop(updates);
+        assert!(updater.is_closed());
+    }
+}